@@ -1,4 +1,8 @@
-pub fn maxf_idx(vec: &Vec<f64>) -> usize {
+use crate::error::ParseError;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+pub fn maxf_idx(vec: &[f64]) -> usize {
     let mut c_idx: usize = 0;
     for i in 0..vec.len() {
         if vec[i] > vec[c_idx] {
@@ -7,3 +11,97 @@ pub fn maxf_idx(vec: &Vec<f64>) -> usize {
     }
     c_idx
 }
+
+pub fn field<'a>(obj: &'a Map<String, Value>, key: &str) -> &'a Value {
+    obj.get(key).unwrap_or(&Value::Null)
+}
+
+pub fn as_object<'a>(val: &'a Value, path: &str) -> Result<&'a Map<String, Value>, ParseError> {
+    val.as_object()
+        .ok_or_else(|| ParseError::new(path, "object"))
+}
+
+pub fn as_array<'a>(val: &'a Value, path: &str) -> Result<&'a Vec<Value>, ParseError> {
+    val.as_array().ok_or_else(|| ParseError::new(path, "array"))
+}
+
+pub fn as_str<'a>(val: &'a Value, path: &str) -> Result<&'a str, ParseError> {
+    val.as_str().ok_or_else(|| ParseError::new(path, "string"))
+}
+
+pub fn as_f64(val: &Value, path: &str) -> Result<f64, ParseError> {
+    val.as_f64().ok_or_else(|| ParseError::new(path, "number"))
+}
+
+pub fn as_float(val: &Value, path: &str) -> Result<f64, ParseError> {
+    /* a number, or one of the names JsonFloat writes for non-finite values */
+    let val = match val {
+        Value::String(name) => JsonFloat::NonFinite(name.clone()).value(),
+        _ => val.as_f64(),
+    };
+    val.ok_or_else(|| ParseError::new(path, "number"))
+}
+
+pub fn as_bool(val: &Value, path: &str) -> Result<bool, ParseError> {
+    /* absent is false */
+    match val {
+        Value::Null => Ok(false),
+        _ => val
+            .as_bool()
+            .ok_or_else(|| ParseError::new(path, "boolean")),
+    }
+}
+
+pub fn as_usize(val: &Value, path: &str) -> Result<usize, ParseError> {
+    val.as_u64()
+        .map(|val| val as usize)
+        .ok_or_else(|| ParseError::new(path, "non-negative integer"))
+}
+
+pub fn stop_on_error<T, E>(done: &mut bool, next: Result<Option<T>, E>) -> Option<Result<T, E>> {
+    /* for readers over a stream: stop after the first error, the rest of the stream is out of step */
+    match next {
+        Ok(Some(item)) => Some(Ok(item)),
+        Ok(None) => {
+            *done = true;
+            None
+        }
+        Err(e) => {
+            *done = true;
+            Some(Err(e))
+        }
+    }
+}
+
+/* json has no infinity or nan, so those are written as "inf", "-inf" and "nan" */
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum JsonFloat {
+    Finite(f64),
+    NonFinite(String),
+}
+
+impl JsonFloat {
+    pub fn value(&self) -> Option<f64> {
+        match self {
+            JsonFloat::Finite(val) => Some(*val),
+            JsonFloat::NonFinite(name) => match name.as_str() {
+                "inf" => Some(f64::INFINITY),
+                "-inf" => Some(f64::NEG_INFINITY),
+                "nan" => Some(f64::NAN),
+                _ => None,
+            },
+        }
+    }
+}
+
+impl From<f64> for JsonFloat {
+    fn from(val: f64) -> JsonFloat {
+        match val {
+            f64::INFINITY => JsonFloat::NonFinite("inf".to_string()),
+            f64::NEG_INFINITY => JsonFloat::NonFinite("-inf".to_string()),
+            _ if val.is_nan() => JsonFloat::NonFinite("nan".to_string()),
+            _ => JsonFloat::Finite(val),
+        }
+    }
+}
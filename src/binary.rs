@@ -0,0 +1,503 @@
+use crate::aux;
+use crate::config::{self, Config};
+use crate::connector::{self, Connector};
+use crate::error::{BinaryError, ParseError};
+use crate::recognizer::{self, Recognizer, RecognizerFeat, ShapeFeat, MAX_KMER_ORDER};
+use crate::{organism, Organism};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::{fs, slice};
+
+/* population file layout, all integers and floats little-endian: */
+/*   header: magic "ORGB", version u16 */
+/*   organism: id u64 (u64::MAX for none), node count u32, nodes */
+/*   node: type u8, payload length u32, payload */
+/* organisms follow each other until end of file, so a single organism is a */
+/* one-record population. payloads by node type: */
+/*   0 pssm: columns u32, log-odds u8, columns * 4 f64 (a, c, g, t per column) */
+/*   1 kpssm: order u32, columns u32, log-odds u8, columns * 4^(order + 1) f64 */
+/*   2 shape: feature u8 (MGW, ProT, HelT, Roll), length u32, mu f64, sigma f64 */
+/*   3 connector: mu f64, sigma f64 */
+/* log-odds scores may be -inf, kept as is. payload lengths leave room for fields */
+/* appended in later versions: readers skip payload bytes after the known fields */
+
+const MAGIC: [u8; 4] = *b"ORGB";
+const VERSION: u16 = 1;
+const NO_ID: u64 = u64::MAX;
+
+const PSSM: u8 = 0;
+const KPSSM: u8 = 1;
+const SHAPE: u8 = 2;
+const CONNECTOR: u8 = 3;
+
+const SHAPE_FEATS: [ShapeFeat; 4] = [
+    ShapeFeat::MGW,
+    ShapeFeat::ProT,
+    ShapeFeat::HelT,
+    ShapeFeat::Roll,
+];
+
+pub struct Writer<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> Writer<W> {
+    pub fn write(&mut self, org: &Organism) -> Result<(), BinaryError> {
+        if org.num_recs() == 0 {
+            return Err(ParseError::new("", "non-empty array of nodes").into());
+        }
+        let id = match org.has_id() {
+            true => org.id() as u64,
+            false => NO_ID,
+        };
+        self.writer.write_all(&id.to_le_bytes())?;
+        self.writer.write_all(&(org.len() as u32).to_le_bytes())?;
+        for i in 0..org.num_recs() {
+            let (node_type, payload) = rec_payload(org.rec_at(i)).map_err(|e| e.at_node(i * 2))?;
+            self.write_node(node_type, &payload)?;
+            if i < org.num_cons() {
+                self.write_node(CONNECTOR, &con_payload(org.con_at(i)))?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> Result<W, BinaryError> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    fn write_node(&mut self, node_type: u8, payload: &[u8]) -> Result<(), BinaryError> {
+        self.writer.write_all(&[node_type])?;
+        self.writer
+            .write_all(&(payload.len() as u32).to_le_bytes())?;
+        self.writer.write_all(payload)?;
+        Ok(())
+    }
+}
+
+pub struct Reader<R: Read> {
+    reader: R,
+    config: Option<Config>,
+    org_idx: usize,
+    done: bool,
+}
+
+impl<R: Read> Reader<R> {
+    fn read_org(&mut self) -> Result<Option<Organism>, BinaryError> {
+        /* a clean end of file is only allowed between organisms */
+        let mut id = [0u8; 8];
+        let mut num_read = 0;
+        while num_read < id.len() {
+            match self.reader.read(&mut id[num_read..]) {
+                Ok(0) if num_read == 0 => return Ok(None),
+                Ok(0) => return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into()),
+                Ok(n) => num_read += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        let id = match u64::from_le_bytes(id) {
+            NO_ID => None,
+            id => Some(id as usize),
+        };
+        let num_nodes = read_u32(&mut self.reader)? as usize;
+        if num_nodes == 0 {
+            return Err(ParseError::new("", "non-empty array of nodes").into());
+        }
+
+        let rec_conf = self.config.as_ref().map(|conf| conf.recognizer());
+        let con_conf = self.config.as_ref().map(|conf| conf.connector());
+        let mut recs: Vec<Recognizer> = Vec::new();
+        let mut cons: Vec<Connector> = Vec::new();
+        for i in 0..num_nodes {
+            let node_type = read_u8(&mut self.reader)?;
+            let len = read_u32(&mut self.reader)? as u64;
+            /* read through take, so a corrupt length cannot allocate more than the file holds */
+            let mut payload: Vec<u8> = Vec::new();
+            (&mut self.reader).take(len).read_to_end(&mut payload)?;
+            if payload.len() as u64 != len {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+            }
+            let mut payload = payload.as_slice();
+            match (node_type, i % 2) {
+                (PSSM | KPSSM | SHAPE, 0) => recs
+                    .push(read_rec(node_type, &mut payload, rec_conf).map_err(|e| e.at_node(i))?),
+                (CONNECTOR, 1) => {
+                    cons.push(read_con(&mut payload, con_conf.cloned()).map_err(|e| e.at_node(i))?)
+                }
+                (_, 0) => {
+                    return Err(ParseError::new(".objectType", "one of pssm, kpssm, shape")
+                        .at_node(i)
+                        .into())
+                }
+                _ => {
+                    return Err(ParseError::new(".objectType", "connector")
+                        .at_node(i)
+                        .into())
+                }
+            }
+        }
+        if recs.len() == cons.len() {
+            return Err(ParseError::new("", "recognizer").at_node(num_nodes).into());
+        }
+
+        let org_conf = self.config.as_ref().map(|conf| conf.organism().clone());
+        Ok(Some(organism(recs, cons, id, org_conf)))
+    }
+}
+
+impl<R: Read> Iterator for Reader<R> {
+    type Item = Result<Organism, BinaryError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let org_idx = self.org_idx;
+        self.org_idx += 1;
+        let org = self.read_org().map_err(|e| e.at_org(org_idx));
+        aux::stop_on_error(&mut self.done, org)
+    }
+}
+
+pub fn writer<W: Write>(mut writer: W) -> Result<Writer<W>, BinaryError> {
+    writer.write_all(&MAGIC)?;
+    writer.write_all(&VERSION.to_le_bytes())?;
+    Ok(Writer { writer })
+}
+
+pub fn reader<R: Read>(mut reader: R, config: Option<&Config>) -> Result<Reader<R>, BinaryError> {
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic).map_err(|e| match e.kind() {
+        io::ErrorKind::UnexpectedEof => BinaryError::MagicError,
+        _ => BinaryError::IOError(e),
+    })?;
+    if magic != MAGIC {
+        return Err(BinaryError::MagicError);
+    }
+    let mut version = [0u8; 2];
+    reader.read_exact(&mut version)?;
+    let version = u16::from_le_bytes(version);
+    if version == 0 || version > VERSION {
+        return Err(BinaryError::VersionError(version, VERSION));
+    }
+    Ok(Reader {
+        reader,
+        config: config.cloned(),
+        org_idx: 0,
+        done: false,
+    })
+}
+
+pub fn write(orgs: &[Organism], org_file: &str) -> Result<(), BinaryError> {
+    let mut org_writer = writer(BufWriter::new(fs::File::create(org_file)?))?;
+    for org in orgs {
+        org_writer.write(org)?;
+    }
+    org_writer.finish()?;
+    Ok(())
+}
+
+pub fn from_file(
+    org_file: &str,
+    conf_file: Option<&str>,
+) -> Result<Reader<BufReader<fs::File>>, BinaryError> {
+    let conf = match conf_file {
+        Some(conf_file) => Some(config::load_files(&[conf_file])?),
+        None => None,
+    };
+    reader(BufReader::new(fs::File::open(org_file)?), conf.as_ref())
+}
+
+pub fn read(org_file: &str, conf_file: Option<&str>) -> Result<Vec<Organism>, BinaryError> {
+    from_file(org_file, conf_file)?.collect()
+}
+
+pub fn to_binary(org: &Organism, org_file: &str) -> Result<(), BinaryError> {
+    write(slice::from_ref(org), org_file)
+}
+
+pub fn from_json(json_file: &str, org_file: &str) -> Result<(), BinaryError> {
+    write(&crate::from_json_list(json_file, None)?, org_file)
+}
+
+pub fn to_json(org_file: &str, json_file: &str) -> Result<(), BinaryError> {
+    Ok(crate::to_json_list(&read(org_file, None)?, json_file)?)
+}
+
+fn rec_payload(rec: &Recognizer) -> Result<(u8, Vec<u8>), ParseError> {
+    let mut payload: Vec<u8> = Vec::with_capacity(rec.matrix().len() * 8 + 16);
+    let node_type = match rec.feat() {
+        RecognizerFeat::Sequence => {
+            payload.extend((rec.num_cols() as u32).to_le_bytes());
+            payload.push(rec.log_odds() as u8);
+            PSSM
+        }
+        RecognizerFeat::Kmer(order) => {
+            payload.extend((order as u32).to_le_bytes());
+            payload.extend((rec.num_cols() as u32).to_le_bytes());
+            payload.push(rec.log_odds() as u8);
+            KPSSM
+        }
+        RecognizerFeat::Shape(feat) => {
+            let feat = match feat {
+                ShapeFeat::MGW => 0,
+                ShapeFeat::ProT => 1,
+                ShapeFeat::HelT => 2,
+                ShapeFeat::Roll => 3,
+                ShapeFeat::None => {
+                    return Err(ParseError::new(".feature", "one of MGW, ProT, HelT, Roll"))
+                }
+            };
+            payload.push(feat);
+            payload.extend((rec.len() as u32).to_le_bytes());
+            payload.extend(rec.mu().to_le_bytes());
+            payload.extend(rec.sigma().to_le_bytes());
+            return Ok((SHAPE, payload));
+        }
+        RecognizerFeat::None => {
+            return Err(ParseError::new(".objectType", "one of pssm, kpssm, shape"))
+        }
+    };
+    for val in rec.matrix() {
+        payload.extend(val.to_le_bytes());
+    }
+    Ok((node_type, payload))
+}
+
+fn con_payload(con: &Connector) -> Vec<u8> {
+    let mut payload: Vec<u8> = Vec::with_capacity(16);
+    payload.extend(con.mu().to_le_bytes());
+    payload.extend(con.sigma().to_le_bytes());
+    payload
+}
+
+fn read_rec(
+    node_type: u8,
+    payload: &mut &[u8],
+    conf: Option<&config::RecognizerConfig>,
+) -> Result<Recognizer, BinaryError> {
+    match node_type {
+        SHAPE => {
+            let feat = *SHAPE_FEATS
+                .get(read_u8(payload)? as usize)
+                .ok_or_else(|| ParseError::new(".feature", "one of MGW, ProT, HelT, Roll"))?;
+            let len = read_u32(payload)? as usize;
+            let mu = read_f64(payload)?;
+            let sigma = read_f64(payload)?;
+            if len == 0 {
+                return Err(ParseError::new(".length", "positive integer").into());
+            }
+            if !mu.is_finite() {
+                return Err(ParseError::new(".mu", "finite number").into());
+            }
+            if !(sigma > 0.0 && sigma.is_finite()) {
+                return Err(ParseError::new(".sigma", "positive number").into());
+            }
+            Ok(recognizer::shape(
+                RecognizerFeat::Shape(feat),
+                len,
+                Some(mu),
+                Some(sigma),
+                conf.cloned(),
+            ))
+        }
+        _ => {
+            let order = match node_type {
+                KPSSM => read_u32(payload)? as usize,
+                _ => 0,
+            };
+            if order > MAX_KMER_ORDER {
+                return Err(ParseError::new(
+                    ".order",
+                    &format!("order of at most {}", MAX_KMER_ORDER),
+                )
+                .into());
+            }
+            let num_cols = read_u32(payload)? as usize;
+            if num_cols == 0 {
+                return Err(ParseError::new(".pwm", "non-empty array").into());
+            }
+            let log_odds = match read_u8(payload)? {
+                0 => false,
+                1 => true,
+                _ => return Err(ParseError::new(".logOdds", "0 or 1").into()),
+            };
+            let col_size = 4_usize.pow(order as u32 + 1);
+            /* the payload was read in full, so its size bounds the matrix */
+            if payload.len() < num_cols * col_size * 8 {
+                return Err(ParseError::new(
+                    ".pwm",
+                    &format!("{} columns of {} numbers", num_cols, col_size),
+                )
+                .into());
+            }
+            let mut matrix: Vec<f64> = Vec::with_capacity(num_cols * col_size);
+            for _ in 0..num_cols * col_size {
+                matrix.push(read_f64(payload)?);
+            }
+            let feat = match node_type {
+                KPSSM => RecognizerFeat::Kmer(order),
+                _ => RecognizerFeat::Sequence,
+            };
+            let mut rec = recognizer::pssm(feat, num_cols + order, Some(matrix), conf.cloned());
+            rec.set_log_odds(log_odds);
+            Ok(rec)
+        }
+    }
+}
+
+fn read_con(
+    payload: &mut &[u8],
+    conf: Option<config::ConnectorConfig>,
+) -> Result<Connector, BinaryError> {
+    let mu = read_f64(payload)?;
+    let sigma = read_f64(payload)?;
+    if !mu.is_finite() {
+        return Err(ParseError::new(".mu", "finite number").into());
+    }
+    if !(sigma > 0.0 && sigma.is_finite()) {
+        return Err(ParseError::new(".sigma", "positive number").into());
+    }
+    Ok(connector::connector(mu, sigma, conf))
+}
+
+fn read_u8(reader: &mut impl Read) -> Result<u8, BinaryError> {
+    let mut buf = [0u8; 1];
+    reader.read_exact(&mut buf)?;
+    Ok(buf[0])
+}
+
+fn read_u32(reader: &mut impl Read) -> Result<u32, BinaryError> {
+    let mut buf = [0u8; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_f64(reader: &mut impl Read) -> Result<f64, BinaryError> {
+    let mut buf = [0u8; 8];
+    reader.read_exact(&mut buf)?;
+    Ok(f64::from_le_bytes(buf))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn orgs() -> Vec<Organism> {
+        let bases = ["a", "c", "g", "t"];
+        let kmer_col: serde_json::Map<String, serde_json::Value> = bases
+            .iter()
+            .flat_map(|a| bases.iter().map(move |b| format!("{}{}", a, b)))
+            .enumerate()
+            .map(|(i, kmer)| (kmer, json!(i as f64 / 3.0 - 2.5)))
+            .collect();
+        let mixed = json!({"version": 2, "id": 4, "nodes": [
+            {"objectType": "kpssm", "order": 1, "pwm": [kmer_col]},
+            {"objectType": "connector", "mu": 1.5, "sigma": 0.7},
+            {"objectType": "shape", "feature": "HelT", "mu": 34.1, "sigma": 1.3, "length": 5},
+            {"objectType": "connector", "mu": -2.0, "sigma": 4.0},
+            {"objectType": "pssm", "pwm": [{"a": 0.1, "c": -1.0 / 3.0, "g": 1e-300, "t": -7.25}]}
+        ]});
+        let single = json!([{"objectType": "pssm", "pwm": [
+            {"a": 1.0, "c": 0.0, "g": 0.0, "t": 0.0},
+            {"a": 0.0, "c": 0.5, "g": 0.5, "t": 0.0}
+        ]}]);
+        vec![
+            crate::from_value(&mixed, None, None, None).unwrap(),
+            crate::from_value(&single, None, None, None).unwrap(),
+        ]
+    }
+
+    fn to_bytes(orgs: &[Organism]) -> Vec<u8> {
+        let mut org_writer = writer(Vec::new()).unwrap();
+        for org in orgs {
+            org_writer.write(org).unwrap();
+        }
+        org_writer.finish().unwrap()
+    }
+
+    #[test]
+    fn round_trips_a_population() {
+        let orgs = orgs();
+        let bytes = to_bytes(&orgs);
+        let back: Vec<Organism> = reader(bytes.as_slice(), None)
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(back.len(), orgs.len());
+        for (org, back) in orgs.iter().zip(&back) {
+            assert_eq!(back.to_value().unwrap(), org.to_value().unwrap());
+        }
+        assert!(!back[1].has_id());
+        assert_eq!(to_bytes(&back), bytes);
+
+        let empty = to_bytes(&[]);
+        assert_eq!(reader(empty.as_slice(), None).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn rejects_bad_headers_and_stops_at_the_first_error() {
+        let bytes = to_bytes(&orgs());
+        assert!(matches!(
+            reader(&b"ORG"[..], None),
+            Err(BinaryError::MagicError)
+        ));
+        let mut newer = bytes.clone();
+        newer[4..6].copy_from_slice(&(VERSION + 1).to_le_bytes());
+        assert!(matches!(
+            reader(newer.as_slice(), None),
+            Err(BinaryError::VersionError(v, VERSION)) if v == VERSION + 1
+        ));
+
+        let truncated = &bytes[..bytes.len() - 3];
+        let read: Vec<_> = reader(truncated, None).unwrap().collect();
+        assert_eq!(read.len(), 2);
+        assert!(read[0].is_ok());
+        assert!(matches!(read[1], Err(BinaryError::IOError(_))));
+
+        /* the first node of the second organism turned into a connector */
+        let mut swapped = bytes.clone();
+        let second = bytes.len() - (8 + 4 + 1 + 4 + 4 + 1 + 2 * 4 * 8);
+        swapped[second + 12] = CONNECTOR;
+        let read: Vec<_> = reader(swapped.as_slice(), None).unwrap().collect();
+        assert_eq!(read.len(), 2);
+        match &read[1] {
+            Err(BinaryError::ParseError(e)) => assert_eq!(e.path(), "[1][0].objectType"),
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn keeps_log_odds_and_non_finite_scores() {
+        let mut orgs = orgs();
+        orgs[1].to_pssm(None);
+        assert!(orgs[1].rec_at(0).matrix().contains(&f64::NEG_INFINITY));
+        let bytes = to_bytes(&orgs);
+        let back: Vec<Organism> = reader(bytes.as_slice(), None)
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        let rec = back[1].rec_at(0);
+        assert!(rec.log_odds());
+        assert_eq!(rec.matrix(), orgs[1].rec_at(0).matrix());
+        assert!(!back[0].rec_at(2).log_odds());
+    }
+
+    #[test]
+    fn skips_payload_bytes_after_the_known_fields() {
+        /* a later version appending a field to the connector of the last organism */
+        let mut bytes = to_bytes(&orgs()[..1]);
+        let con = bytes.len() - (1 + 4 + (4 + 1 + 4 * 8)) - (1 + 4 + 16);
+        assert_eq!(bytes[con], CONNECTOR);
+        bytes[con + 1..con + 5].copy_from_slice(&20_u32.to_le_bytes());
+        bytes.splice(con + 5 + 16..con + 5 + 16, [7, 7, 7, 7]);
+        let back: Vec<Organism> = reader(bytes.as_slice(), None)
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(back.len(), 1);
+        assert_eq!(back[0].to_value().unwrap(), orgs()[0].to_value().unwrap());
+    }
+}
@@ -0,0 +1,204 @@
+use crate::config::{Config, ConnectorConfig, OrganismConfig, RecognizerConfig};
+use crate::connector::{self, Connector};
+use crate::error::OrganismError;
+use crate::recognizer::Recognizer;
+use crate::{organism, Organism};
+
+#[derive(Debug, Clone)]
+enum Node {
+    Rec(Box<Recognizer>),
+    Con(f64, f64),
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct OrganismBuilder {
+    nodes: Vec<Node>,
+    id: Option<usize>,
+    org_conf: Option<OrganismConfig>,
+    rec_conf: Option<RecognizerConfig>,
+    con_conf: Option<ConnectorConfig>,
+    max_seq_len: Option<usize>,
+}
+
+impl OrganismBuilder {
+    pub fn recognizer(mut self, rec: Recognizer) -> OrganismBuilder {
+        self.nodes.push(Node::Rec(Box::new(rec)));
+        self
+    }
+
+    pub fn connector(mut self, mu: f64, sigma: f64) -> OrganismBuilder {
+        self.nodes.push(Node::Con(mu, sigma));
+        self
+    }
+
+    pub fn config(mut self, config: &Config) -> OrganismBuilder {
+        self.org_conf = Some(config.organism().clone());
+        self.rec_conf = Some(config.recognizer().clone());
+        self.con_conf = Some(config.connector().clone());
+        self
+    }
+
+    pub fn organism_config(mut self, org_conf: OrganismConfig) -> OrganismBuilder {
+        self.org_conf = Some(org_conf);
+        self
+    }
+
+    pub fn recognizer_config(mut self, rec_conf: RecognizerConfig) -> OrganismBuilder {
+        self.rec_conf = Some(rec_conf);
+        self
+    }
+
+    pub fn connector_config(mut self, con_conf: ConnectorConfig) -> OrganismBuilder {
+        self.con_conf = Some(con_conf);
+        self
+    }
+
+    pub fn max_seq_len(mut self, max_seq_len: usize) -> OrganismBuilder {
+        self.max_seq_len = Some(max_seq_len);
+        self
+    }
+
+    pub fn id(mut self, id: usize) -> OrganismBuilder {
+        self.id = Some(id);
+        self
+    }
+
+    pub fn build(self) -> Result<Organism, OrganismError> {
+        /* nodes must read rec, con, rec, ..., rec; connectors are precomputed up to */
+        /* the explicit max_seq_len, or else the connector config's MAX_SEQ_LEN */
+        let num_nodes = self.nodes.len();
+        if let Some(org_conf) = &self.org_conf {
+            if num_nodes < org_conf.min_nodes() || num_nodes > org_conf.max_nodes() {
+                return Err(OrganismError::NodeBoundsError(
+                    num_nodes,
+                    org_conf.min_nodes(),
+                    org_conf.max_nodes(),
+                ));
+            }
+        }
+        let mut recs: Vec<Recognizer> = Vec::with_capacity(num_nodes / 2 + 1);
+        let mut cons: Vec<Connector> = Vec::with_capacity(num_nodes / 2);
+        for (node_idx, node) in self.nodes.into_iter().enumerate() {
+            match (node, node_idx % 2) {
+                (Node::Rec(mut rec), 0) => {
+                    if rec.is_empty() {
+                        return Err(OrganismError::EmptyRecognizerError(node_idx));
+                    }
+                    if self.rec_conf.is_some() {
+                        rec.set_config(self.rec_conf.clone());
+                    }
+                    recs.push(*rec);
+                }
+                (Node::Con(mu, sigma), 1) => {
+                    if !mu.is_finite() || !sigma.is_finite() || sigma <= 0.0 {
+                        return Err(OrganismError::InvalidConnectorError(node_idx));
+                    }
+                    let mut con = connector::connector(mu, sigma, self.con_conf.clone());
+                    if let Some(max_seq_len) = self.max_seq_len {
+                        con.precompute_from_size(max_seq_len);
+                    }
+                    if con.is_empty() {
+                        return Err(OrganismError::PrecomputeError(node_idx));
+                    }
+                    cons.push(con);
+                }
+                _ => return Err(OrganismError::NodeOrderError(node_idx)),
+            }
+        }
+
+        if num_nodes.is_multiple_of(2) {
+            /* empty, or ends on a connector */
+            return Err(OrganismError::NodeOrderError(num_nodes));
+        }
+        Ok(organism(recs, cons, self.id, self.org_conf))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recognizer::{pssm, RecognizerFeat};
+
+    fn rec(len: usize) -> Recognizer {
+        pssm(
+            RecognizerFeat::Sequence,
+            len,
+            Some(vec![0.0; len * 4]),
+            None,
+        )
+    }
+
+    #[test]
+    fn builds_alternating_nodes() {
+        let org = Organism::builder()
+            .recognizer(rec(3))
+            .connector(5.0, 2.0)
+            .recognizer(rec(4))
+            .connector_config(ConnectorConfig::default())
+            .id(7)
+            .build()
+            .unwrap();
+        assert_eq!(org.num_recs(), 2);
+        assert_eq!(org.num_cons(), 1);
+        assert_eq!(org.id(), 7);
+    }
+
+    #[test]
+    fn rejects_invalid_organisms() {
+        let err = |builder: OrganismBuilder| builder.max_seq_len(50).build().unwrap_err();
+        assert!(matches!(
+            err(Organism::builder()),
+            OrganismError::NodeOrderError(0)
+        ));
+        assert!(matches!(
+            err(Organism::builder().recognizer(rec(3)).connector(5.0, 2.0)),
+            OrganismError::NodeOrderError(2)
+        ));
+        assert!(matches!(
+            err(Organism::builder().connector(5.0, 2.0).recognizer(rec(3))),
+            OrganismError::NodeOrderError(0)
+        ));
+        assert!(matches!(
+            err(Organism::builder().recognizer(rec(3)).recognizer(rec(3))),
+            OrganismError::NodeOrderError(1)
+        ));
+        assert!(matches!(
+            err(Organism::builder().recognizer(rec(0))),
+            OrganismError::EmptyRecognizerError(0)
+        ));
+        for (mu, sigma) in [(f64::NAN, 2.0), (5.0, 0.0), (5.0, f64::INFINITY)] {
+            assert!(matches!(
+                err(Organism::builder()
+                    .recognizer(rec(3))
+                    .connector(mu, sigma)
+                    .recognizer(rec(3))),
+                OrganismError::InvalidConnectorError(1)
+            ));
+        }
+        assert!(matches!(
+            Organism::builder()
+                .recognizer(rec(3))
+                .connector(5.0, 2.0)
+                .recognizer(rec(3))
+                .build()
+                .unwrap_err(),
+            OrganismError::PrecomputeError(1)
+        ));
+        assert!(matches!(
+            err(Organism::builder()
+                .recognizer(rec(3))
+                .organism_config(OrganismConfig::default())
+                .connector(5.0, 2.0)
+                .recognizer(rec(3))
+                .connector(5.0, 2.0)
+                .recognizer(rec(3))
+                .connector(5.0, 2.0)
+                .recognizer(rec(3))
+                .connector(5.0, 2.0)
+                .recognizer(rec(3))
+                .connector(5.0, 2.0)
+                .recognizer(rec(3))),
+            OrganismError::NodeBoundsError(11, 1, 9)
+        ));
+    }
+}
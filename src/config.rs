@@ -1,12 +1,575 @@
+use crate::error::ConfigError;
+use crate::fitness::{self, CumulativeFit};
+use crate::selection;
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::path::Path;
+use std::{env, fs};
+
+const RUN_MODES: [&str; 2] = ["serial", "parallel"];
+const POPULATION_ORIGINS: [&str; 2] = ["random", "file"];
+const POPULATION_FILL_TYPES: [&str; 2] = ["random", "copy"];
+const END_WHILE_METHODS: [&str; 2] = ["iterations", "fitness"];
+const ENERGY_THRESHOLD_METHODS: [&str; 3] = ["fixed", "percentile", "organism"];
+const NODE_METHODS: [&str; 2] = ["intelligent", "blind"];
+const MUTATORS: [&str; 2] = ["linear", "standard"];
+const SECTIONS: [&str; 5] = [
+    "main",
+    "organism",
+    "organismFactory",
+    "recognizer",
+    "connector",
+];
+const ENV_PREFIX: &str = "ORGANISM__";
 
 #[derive(Deserialize, Serialize, Default, Debug, Clone)]
+#[serde(default)]
+pub struct Config {
+    main: MainConfig,
+    organism: OrganismConfig,
+    #[serde(rename = "organismFactory")]
+    organism_factory: FactoryConfig,
+    recognizer: RecognizerConfig,
+    connector: ConnectorConfig,
+}
+
+impl Config {
+    pub fn main(&self) -> &MainConfig {
+        &self.main
+    }
+    pub fn main_mut(&mut self) -> &mut MainConfig {
+        &mut self.main
+    }
+    pub fn organism(&self) -> &OrganismConfig {
+        &self.organism
+    }
+    pub fn organism_factory(&self) -> &FactoryConfig {
+        &self.organism_factory
+    }
+    pub fn recognizer(&self) -> &RecognizerConfig {
+        &self.recognizer
+    }
+    pub fn connector(&self) -> &ConnectorConfig {
+        &self.connector
+    }
+
+    pub fn into_parts(
+        self,
+    ) -> (
+        MainConfig,
+        OrganismConfig,
+        FactoryConfig,
+        RecognizerConfig,
+        ConnectorConfig,
+    ) {
+        (
+            self.main,
+            self.organism,
+            self.organism_factory,
+            self.recognizer,
+            self.connector,
+        )
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        /* keys are reported as section.KEY, as they appear in config.json */
+        let main = &self.main;
+        one_of("main.RUN_MODE", &main.run_mode, &RUN_MODES)?;
+        positive("main.POPULATION_LENGTH", main.population_length as f64)?;
+        one_of(
+            "main.POPULATION_ORIGIN",
+            &main.population_origin,
+            &POPULATION_ORIGINS,
+        )?;
+        one_of(
+            "main.POPULATION_FILL_TYPE",
+            &main.population_fill_type,
+            &POPULATION_FILL_TYPES,
+        )?;
+        if main.fitness_function != "cumulative"
+            && fitness::from_name(&main.fitness_function).is_err()
+        {
+            return Err(unknown("main.FITNESS_FUNCTION", &main.fitness_function));
+        }
+        one_of(
+            "main.END_WHILE_METHOD",
+            &main.end_while_method,
+            &END_WHILE_METHODS,
+        )?;
+        probability("main.THRESHOLD", main.threshold)?;
+        if selection::from_name(&main.selection_method).is_err() {
+            return Err(unknown("main.SELECTION_METHOD", &main.selection_method));
+        }
+        bounds(
+            "main.ELITISM",
+            main.elitism as f64,
+            "main.POPULATION_LENGTH",
+            main.population_length as f64,
+        )?;
+
+        let org = &self.organism;
+        if CumulativeFit::from_name(&org.cumulative_fit_method).is_err() {
+            return Err(unknown(
+                "organism.CUMULATIVE_FIT_METHOD",
+                &org.cumulative_fit_method,
+            ));
+        }
+        one_of(
+            "organism.ENERGY_THRESHOLD_METHOD",
+            &org.energy_threshold_method,
+            &ENERGY_THRESHOLD_METHODS,
+        )?;
+        one_of(
+            "organism.INSERTION_METHOD",
+            &org.insertion_method,
+            &NODE_METHODS,
+        )?;
+        one_of(
+            "organism.DELETION_METHOD",
+            &org.deletion_method,
+            &NODE_METHODS,
+        )?;
+        probability(
+            "organism.MUTATE_PROBABILITY_NODE_MUTATION",
+            org.mutate_probability_node_mutation,
+        )?;
+        probability(
+            "organism.MUTATE_PROBABILITY_DELETE_RECOGNIZER",
+            org.mutate_probability_delete_recognizer,
+        )?;
+        probability(
+            "organism.MUTATE_PROBABILITY_INSERT_RECOGNIZER",
+            org.mutate_probability_insert_recognizer,
+        )?;
+        probability(
+            "organism.MUTATE_PROBABILITY_SUBSTITUTE_PSSM",
+            org.mutate_probability_substitute_pssm,
+        )?;
+        positive("organism.MAX_NODES", org.max_nodes as f64)?;
+        bounds(
+            "organism.MIN_NODES",
+            org.min_nodes as f64,
+            "organism.MAX_NODES",
+            org.max_nodes as f64,
+        )?;
+
+        validate_factory(&self.organism_factory)?;
+
+        let rec = &self.recognizer;
+        probability(
+            "recognizer.MUTATE_PROBABILITY_RANDOM_COL",
+            rec.mutate_probability_random_col,
+        )?;
+        probability(
+            "recognizer.MUTATE_PROBABILITY_MUTATE_COL",
+            rec.mutate_probability_mutate_col,
+        )?;
+        probability(
+            "recognizer.MUTATE_PROBABILITY_FLIP_COL",
+            rec.mutate_probability_flip_col,
+        )?;
+        probability(
+            "recognizer.MUTATE_PROBABILITY_FLIP_ROW",
+            rec.mutate_probability_flip_row,
+        )?;
+        probability(
+            "recognizer.MUTATE_PROBABILITY_SHIFT_LEFT",
+            rec.mutate_probability_shift_left,
+        )?;
+        probability(
+            "recognizer.MUTATE_PROBABILITY_SHIFT_RIGHT",
+            rec.mutate_probability_shift_right,
+        )?;
+        probability(
+            "recognizer.MUTATE_PROBABILITY_INCREASE_PWM",
+            rec.mutate_probability_increase_pwm,
+        )?;
+        probability(
+            "recognizer.MUTATE_PROBABILITY_DECREASE_PWM",
+            rec.mutate_probability_decrease_pwm,
+        )?;
+        positive("recognizer.MIN_COLUMNS", rec.min_columns as f64)?;
+        bounds(
+            "recognizer.MIN_COLUMNS",
+            rec.min_columns as f64,
+            "recognizer.MAX_COLUMNS",
+            rec.max_columns as f64,
+        )?;
+        probability(
+            "recognizer.UPPER_PRINT_PROBABILITY",
+            rec.upper_print_probability,
+        )?;
+        non_negative("recognizer.PSEUDO_COUNT", rec.pseudo_count)?;
+
+        let con = &self.connector;
+        probability(
+            "connector.MUTATE_PROBABILITY_SIGMA",
+            con.mutate_probability_sigma,
+        )?;
+        probability("connector.MUTATE_PROBABILITY_MU", con.mutate_probability_mu)?;
+        probability(
+            "connector.MUTATE_PROBABILITY_SWAP",
+            con.mutate_probability_swap,
+        )?;
+        positive("connector.MUTATE_VARIANCE_SIGMA", con.mutate_variance_sigma)?;
+        positive("connector.MUTATE_VARIANCE_MU", con.mutate_variance_mu)?;
+        one_of("connector.SIGMA_MUTATOR", &con.sigma_mutator, &MUTATORS)?;
+        one_of("connector.MU_MUTATOR", &con.mu_mutator, &MUTATORS)?;
+        positive("connector.MAX_SEQ_LEN", con.max_seq_len as f64)?;
+        Ok(())
+    }
+}
+
+fn unknown(key: &str, val: &str) -> ConfigError {
+    ConfigError::UnknownMethodError(key.to_string(), val.to_string())
+}
+
+fn one_of(key: &str, val: &str, allowed: &[&str]) -> Result<(), ConfigError> {
+    match allowed.contains(&val) {
+        true => Ok(()),
+        false => Err(unknown(key, val)),
+    }
+}
+
+fn probability(key: &str, val: f64) -> Result<(), ConfigError> {
+    match (0.0..=1.0).contains(&val) {
+        true => Ok(()),
+        false => Err(ConfigError::ProbabilityError(key.to_string(), val)),
+    }
+}
+
+fn positive(key: &str, val: f64) -> Result<(), ConfigError> {
+    match val > 0.0 {
+        true => Ok(()),
+        false => Err(ConfigError::NonPositiveError(key.to_string(), val)),
+    }
+}
+
+pub(crate) fn validate_factory(fac: &FactoryConfig) -> Result<(), ConfigError> {
+    /* also run by factory::factory, which can be handed configs that skipped validate */
+    probability(
+        "organismFactory.RECOMBINATION_PROBABILITY",
+        fac.recombination_probability,
+    )?;
+    positive(
+        "organismFactory.NUM_RECOGNIZERS_LAMBDA_PARAM",
+        fac.num_recognizers_lambda_param,
+    )?;
+    bounds(
+        "organismFactory.MIN_MU",
+        fac.min_mu,
+        "organismFactory.MAX_MU",
+        fac.max_mu,
+    )?;
+    positive("organismFactory.MIN_SIGMA", fac.min_sigma)?;
+    bounds(
+        "organismFactory.MIN_SIGMA",
+        fac.min_sigma,
+        "organismFactory.MAX_SIGMA",
+        fac.max_sigma,
+    )?;
+    positive("organismFactory.PWM_LENGTH", fac.pwm_length as f64)?;
+    Ok(())
+}
+
+pub(crate) fn non_negative(key: &str, val: f64) -> Result<(), ConfigError> {
+    match val >= 0.0 && val.is_finite() {
+        true => Ok(()),
+        false => Err(ConfigError::NegativeError(key.to_string(), val)),
+    }
+}
+
+fn bounds(min_key: &str, min: f64, max_key: &str, max: f64) -> Result<(), ConfigError> {
+    match min <= max {
+        true => Ok(()),
+        false => Err(ConfigError::BoundsError(
+            min_key.to_string(),
+            max_key.to_string(),
+        )),
+    }
+}
+
+pub fn config(
+    main: MainConfig,
+    organism: OrganismConfig,
+    organism_factory: FactoryConfig,
+    recognizer: RecognizerConfig,
+    connector: ConnectorConfig,
+) -> Config {
+    Config {
+        main,
+        organism,
+        organism_factory,
+        recognizer,
+        connector,
+    }
+}
+
+pub fn load(conf_files: &[&str]) -> Result<Config, ConfigError> {
+    /* later files override earlier ones and ORGANISM__* variables override every file, */
+    /* anything left unset keeps its default */
+    let mut conf = merge_files(conf_files)?;
+    apply_env(&mut conf, env::vars())?;
+    Ok(serde_json::from_value(conf)?)
+}
+
+pub fn load_files(conf_files: &[&str]) -> Result<Config, ConfigError> {
+    /* as load, without the environment, for configs that travel with saved organisms */
+    Ok(serde_json::from_value(merge_files(conf_files)?)?)
+}
+
+fn merge_files(conf_files: &[&str]) -> Result<Value, ConfigError> {
+    let mut conf = Value::Object(Map::new());
+    for conf_file in conf_files {
+        merge(&mut conf, value_from_file(conf_file)?);
+    }
+    Ok(conf)
+}
+
+pub fn value_from_file(conf_file: &str) -> Result<Value, ConfigError> {
+    let contents = fs::read_to_string(conf_file)?;
+    match Path::new(conf_file)
+        .extension()
+        .and_then(|ext| ext.to_str())
+    {
+        Some("toml") => Ok(toml::from_str(&contents)?),
+        _ => Ok(serde_json::from_str(&contents)?),
+    }
+}
+
+pub fn merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, val) in overlay {
+                merge(base.entry(key).or_insert(Value::Null), val);
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+pub fn apply_env(
+    conf: &mut Value,
+    vars: impl IntoIterator<Item = (String, String)>,
+) -> Result<(), ConfigError> {
+    /* ORGANISM__<SECTION>__<KEY>, the section matched ignoring case and underscores; */
+    /* unknown keys are skipped and values are parsed as the type of */
+    /* the key's default, so string keys keep numeric looking values as strings */
+    let defaults = serde_json::to_value(Config::default())?;
+    for (name, val) in vars {
+        let path = match name.strip_prefix(ENV_PREFIX) {
+            Some(path) => path,
+            None => continue,
+        };
+        let target = path.split_once("__").and_then(|(section, key)| {
+            let section = SECTIONS
+                .iter()
+                .find(|known| known.eq_ignore_ascii_case(&section.replace('_', "")))?;
+            let key = key.to_uppercase();
+            let default = defaults[section].get(&key)?;
+            Some((section, key, default))
+        });
+        let (section, key, default) = match target {
+            Some(target) => target,
+            None => continue,
+        };
+        let val = match default {
+            Value::String(_) => Value::String(val),
+            _ => match serde_json::from_str::<Value>(&val) {
+                Ok(parsed) if same_type(default, &parsed) => parsed,
+                _ => return Err(ConfigError::EnvValueError(name, val)),
+            },
+        };
+        merge(conf, json!({ *section: { key: val } }));
+    }
+    Ok(())
+}
+
+fn same_type(default: &Value, val: &Value) -> bool {
+    /* a null default is an unset option, which takes a number */
+    match (default, val) {
+        (Value::Null, Value::Number(_) | Value::Null) => true,
+        (Value::Number(default), Value::Number(val)) => default.is_f64() || !val.is_f64(),
+        (Value::Bool(_), Value::Bool(_)) => true,
+        (Value::Array(_), Value::Array(_)) => true,
+        (Value::Object(_), Value::Object(_)) => true,
+        _ => false,
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(tag = "main")]
+#[serde(rename_all = "UPPERCASE")]
+#[serde(default)]
+pub struct MainConfig {
+    run_mode: String,
+    num_threads: usize,
+    population_length: usize,
+    population_origin: String,
+    population_fill_type: String,
+    dataset_base_path_dir: String,
+    result_base_path_dir: String,
+    result_test_base_path_dir: String,
+    positive_filename: String,
+    negative_filename: String,
+    generated_neg_set_size: usize,
+    generated_neg_set_kmer_len: usize,
+    input_filename: String,
+    output_filename: String,
+    max_sequences_to_fit_pos: usize,
+    max_sequences_to_fit_neg: usize,
+    random_shuffle_sampling_pos: bool,
+    random_shuffle_sampling_neg: bool,
+    fitness_function: String,
+    genome_length: usize,
+    end_while_method: String,
+    min_iterations: usize,
+    min_fitness: f64,
+    threshold: f64,
+    periodic_org_export: usize,
+    periodic_pop_export: usize,
+    selection_method: String,
+    elitism: usize,
+    seed: Option<u64>,
+}
+
+impl MainConfig {
+    pub fn run_mode(&self) -> &str {
+        &self.run_mode
+    }
+    pub fn num_threads(&self) -> usize {
+        self.num_threads
+    }
+    pub fn population_length(&self) -> usize {
+        self.population_length
+    }
+    pub fn population_origin(&self) -> &str {
+        &self.population_origin
+    }
+    pub fn population_fill_type(&self) -> &str {
+        &self.population_fill_type
+    }
+    pub fn dataset_base_path_dir(&self) -> &str {
+        &self.dataset_base_path_dir
+    }
+    pub fn result_base_path_dir(&self) -> &str {
+        &self.result_base_path_dir
+    }
+    pub fn result_test_base_path_dir(&self) -> &str {
+        &self.result_test_base_path_dir
+    }
+    pub fn positive_filename(&self) -> &str {
+        &self.positive_filename
+    }
+    pub fn negative_filename(&self) -> &str {
+        &self.negative_filename
+    }
+    pub fn generated_neg_set_size(&self) -> usize {
+        self.generated_neg_set_size
+    }
+    pub fn generated_neg_set_kmer_len(&self) -> usize {
+        self.generated_neg_set_kmer_len
+    }
+    pub fn input_filename(&self) -> &str {
+        &self.input_filename
+    }
+    pub fn output_filename(&self) -> &str {
+        &self.output_filename
+    }
+    pub fn max_sequences_to_fit_pos(&self) -> usize {
+        self.max_sequences_to_fit_pos
+    }
+    pub fn max_sequences_to_fit_neg(&self) -> usize {
+        self.max_sequences_to_fit_neg
+    }
+    pub fn random_shuffle_sampling_pos(&self) -> bool {
+        self.random_shuffle_sampling_pos
+    }
+    pub fn random_shuffle_sampling_neg(&self) -> bool {
+        self.random_shuffle_sampling_neg
+    }
+    pub fn fitness_function(&self) -> &str {
+        &self.fitness_function
+    }
+    pub fn genome_length(&self) -> usize {
+        self.genome_length
+    }
+    pub fn end_while_method(&self) -> &str {
+        &self.end_while_method
+    }
+    pub fn min_iterations(&self) -> usize {
+        self.min_iterations
+    }
+    pub fn min_fitness(&self) -> f64 {
+        self.min_fitness
+    }
+    pub fn threshold(&self) -> f64 {
+        self.threshold
+    }
+    pub fn periodic_org_export(&self) -> usize {
+        self.periodic_org_export
+    }
+    pub fn periodic_pop_export(&self) -> usize {
+        self.periodic_pop_export
+    }
+    pub fn selection_method(&self) -> &str {
+        &self.selection_method
+    }
+    pub fn elitism(&self) -> usize {
+        self.elitism
+    }
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = Some(seed);
+    }
+}
+
+impl Default for MainConfig {
+    fn default() -> MainConfig {
+        MainConfig {
+            run_mode: "serial".to_string(),
+            num_threads: 0,
+            population_length: 50,
+            population_origin: "random".to_string(),
+            population_fill_type: "random".to_string(),
+            dataset_base_path_dir: "datasets/".to_string(),
+            result_base_path_dir: "results/".to_string(),
+            result_test_base_path_dir: "resultsTEST/".to_string(),
+            positive_filename: "positive_dataset_7_100.fas".to_string(),
+            negative_filename: String::new(),
+            generated_neg_set_size: 334,
+            generated_neg_set_kmer_len: 4,
+            input_filename: "organism.json".to_string(),
+            output_filename: "output.txt".to_string(),
+            max_sequences_to_fit_pos: 20,
+            max_sequences_to_fit_neg: 20,
+            random_shuffle_sampling_pos: true,
+            random_shuffle_sampling_neg: true,
+            fitness_function: "welchs".to_string(),
+            genome_length: 4600000,
+            end_while_method: "iterations".to_string(),
+            min_iterations: 10000,
+            min_fitness: 100.0,
+            threshold: 0.05,
+            periodic_org_export: 5,
+            periodic_pop_export: 5,
+            selection_method: "tournament:2".to_string(),
+            elitism: 1,
+            seed: None,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(tag = "organism")]
 #[serde(rename_all = "UPPERCASE")]
+#[serde(default)]
 pub struct OrganismConfig {
     cumulative_fit_method: String,
     energy_threshold_method: String,
-    energy_threshold_param: usize,
+    energy_threshold_param: f64,
     insertion_method: String,
     deletion_method: String,
     mutate_probability_node_mutation: f64,
@@ -25,8 +588,8 @@ impl OrganismConfig {
     pub fn energy_threshold_method(&self) -> &str {
         &self.energy_threshold_method
     }
-    pub fn energy_threshold_param(&self) -> usize {
-        self.clone().energy_threshold_param
+    pub fn energy_threshold_param(&self) -> f64 {
+        self.energy_threshold_param
     }
     pub fn insertion_method(&self) -> &str {
         &self.insertion_method
@@ -57,9 +620,86 @@ impl OrganismConfig {
     }
 }
 
-#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+impl Default for OrganismConfig {
+    fn default() -> OrganismConfig {
+        OrganismConfig {
+            cumulative_fit_method: "mean".to_string(),
+            energy_threshold_method: "organism".to_string(),
+            energy_threshold_param: 0.0,
+            insertion_method: "intelligent".to_string(),
+            deletion_method: "intelligent".to_string(),
+            mutate_probability_node_mutation: 0.0,
+            mutate_probability_delete_recognizer: 0.2,
+            mutate_probability_insert_recognizer: 0.1,
+            mutate_probability_substitute_pssm: 0.075,
+            min_nodes: 1,
+            max_nodes: 9,
+            precompute: true,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(tag = "organismFactory")]
+#[serde(rename_all = "UPPERCASE")]
+#[serde(default)]
+pub struct FactoryConfig {
+    recombination_probability: f64,
+    num_recognizers_lambda_param: f64,
+    min_mu: f64,
+    max_mu: f64,
+    min_sigma: f64,
+    max_sigma: f64,
+    pwm_length: usize,
+    pwm_num_of_binding_sites: usize,
+}
+
+impl FactoryConfig {
+    pub fn recombination_probability(&self) -> f64 {
+        self.recombination_probability
+    }
+    pub fn num_recognizers_lambda_param(&self) -> f64 {
+        self.num_recognizers_lambda_param
+    }
+    pub fn min_mu(&self) -> f64 {
+        self.min_mu
+    }
+    pub fn max_mu(&self) -> f64 {
+        self.max_mu
+    }
+    pub fn min_sigma(&self) -> f64 {
+        self.min_sigma
+    }
+    pub fn max_sigma(&self) -> f64 {
+        self.max_sigma
+    }
+    pub fn pwm_length(&self) -> usize {
+        self.pwm_length
+    }
+    pub fn pwm_num_of_binding_sites(&self) -> usize {
+        self.pwm_num_of_binding_sites
+    }
+}
+
+impl Default for FactoryConfig {
+    fn default() -> FactoryConfig {
+        FactoryConfig {
+            recombination_probability: 0.33,
+            num_recognizers_lambda_param: 2.5,
+            min_mu: 0.0,
+            max_mu: 50.0,
+            min_sigma: 1.0,
+            max_sigma: 3.0,
+            pwm_length: 4,
+            pwm_num_of_binding_sites: 100,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(tag = "recognizer")]
 #[serde(rename_all = "UPPERCASE")]
+#[serde(default)]
 pub struct RecognizerConfig {
     mutate_probability_random_col: f64,
     mutate_probability_mutate_col: f64,
@@ -130,9 +770,30 @@ impl RecognizerConfig {
     }
 }
 
+impl Default for RecognizerConfig {
+    fn default() -> RecognizerConfig {
+        RecognizerConfig {
+            mutate_probability_random_col: 0.075,
+            mutate_probability_mutate_col: 0.35,
+            mutate_probability_flip_col: 0.15,
+            mutate_probability_flip_row: 0.05,
+            mutate_probability_shift_left: 0.15,
+            mutate_probability_shift_right: 0.15,
+            mutate_probability_increase_pwm: 0.1,
+            mutate_probability_decrease_pwm: 0.1,
+            min_columns: 3,
+            max_columns: 10,
+            upper_print_probability: 0.75,
+            pseudo_count: 1e-10,
+            scan_reverse_complement: false,
+        }
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(tag = "connector")]
 #[serde(rename_all = "UPPERCASE")]
+#[serde(default)]
 pub struct ConnectorConfig {
     mutate_probability_sigma: f64,
     mutate_probability_mu: f64,
@@ -170,3 +831,71 @@ impl ConnectorConfig {
         self.max_seq_len
     }
 }
+
+impl Default for ConnectorConfig {
+    fn default() -> ConnectorConfig {
+        ConnectorConfig {
+            mutate_probability_sigma: 0.25,
+            mutate_probability_mu: 0.25,
+            mutate_probability_swap: 0.2,
+            mutate_variance_sigma: 5.0,
+            mutate_variance_mu: 5.0,
+            sigma_mutator: "linear".to_string(),
+            mu_mutator: "standard".to_string(),
+            max_seq_len: 200,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(vars: &[(&str, &str)]) -> Result<Config, ConfigError> {
+        let mut conf = Value::Object(Map::new());
+        let vars = vars
+            .iter()
+            .map(|(name, val)| (name.to_string(), val.to_string()));
+        apply_env(&mut conf, vars)?;
+        Ok(serde_json::from_value(conf)?)
+    }
+
+    #[test]
+    fn env_overrides_parse_by_field_type() {
+        let conf = env(&[
+            ("ORGANISM__MAIN__POSITIVE_FILENAME", "123"),
+            ("ORGANISM__MAIN__POPULATION_LENGTH", "7"),
+            ("ORGANISM__MAIN__SEED", "42"),
+            ("ORGANISM__ORGANISM_FACTORY__MAX_MU", "80"),
+            ("ORGANISM__CONNECTOR__MAX_SEQ_LEN", "300"),
+            ("OTHER__MAIN__POPULATION_LENGTH", "x"),
+        ])
+        .unwrap();
+        assert_eq!(conf.main().positive_filename(), "123");
+        assert_eq!(conf.main().population_length(), 7);
+        assert_eq!(conf.main().seed(), Some(42));
+        assert_eq!(conf.organism_factory().max_mu(), 80.0);
+        assert_eq!(conf.connector().max_seq_len(), 300);
+    }
+
+    #[test]
+    fn env_overrides_skip_unknown_keys_and_reject_bad_values() {
+        let conf = env(&[
+            ("ORGANISM__MAIN__NO_SUCH_KEY", "1"),
+            ("ORGANISM__NO_SUCH_SECTION__RUN_MODE", "parallel"),
+            ("ORGANISM__MALFORMED", "1"),
+        ])
+        .unwrap();
+        assert_eq!(conf.main().run_mode(), "serial");
+        for (name, val) in [
+            ("ORGANISM__MAIN__POPULATION_LENGTH", "many"),
+            ("ORGANISM__MAIN__POPULATION_LENGTH", "1.5"),
+            ("ORGANISM__MAIN__RANDOM_SHUFFLE_SAMPLING_POS", "1"),
+        ] {
+            assert!(matches!(
+                env(&[(name, val)]),
+                Err(ConfigError::EnvValueError(..))
+            ));
+        }
+    }
+}
@@ -1,10 +1,17 @@
+use crate::aux;
 use crate::config::ConnectorConfig;
-use crate::error::ConnectorError;
+use crate::error::{ConnectorError, ParseError};
 use num_integer::binomial;
+use rand::distributions::Distribution;
+use rand::Rng;
+use serde::{Deserialize, Serialize, Serializer};
 use serde_json::Value;
 use statrs::distribution::{Continuous, ContinuousCDF, Normal};
 
-#[derive(Debug, Clone)]
+const MIN_SIGMA: f64 = 1e-3;
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(try_from = "ConnectorData")]
 pub struct Connector {
     mu: f64,
     sigma: f64,
@@ -47,6 +54,10 @@ impl Connector {
         self.pdf.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.pdf.is_empty()
+    }
+
     pub fn set_mu(&mut self, mu: f64) {
         self.mu = mu;
     }
@@ -63,6 +74,43 @@ impl Connector {
         self.sigma = (self.sigma * self.sigma + sigma * sigma).sqrt()
     }
 
+    pub fn set_config(&mut self, config: Option<ConnectorConfig>) {
+        /* the precomputed pdf/cdf depend on MAX_SEQ_LEN, so rebuild rather than swap */
+        *self = connector(self.mu, self.sigma, config);
+    }
+
+    pub fn refresh(&mut self) {
+        let len = self.len();
+        self.alt = Normal::new(self.mu, self.sigma).expect("invalid mu or sigma for connector");
+        self.precompute_from_size(len);
+    }
+
+    pub fn mutate(&mut self, rng: &mut impl Rng) {
+        let conf = match &self.config {
+            Some(conf) => conf.clone(),
+            None => return,
+        };
+
+        let mut changed = false;
+        if rng.gen::<f64>() < conf.mutate_probability_mu() {
+            let delta = perturb(conf.mu_mutator(), conf.mutate_variance_mu(), rng);
+            self.mu = (self.mu + delta).max(0.0);
+            changed = true;
+        }
+        if rng.gen::<f64>() < conf.mutate_probability_sigma() {
+            let delta = perturb(conf.sigma_mutator(), conf.mutate_variance_sigma(), rng);
+            self.sigma = (self.sigma + delta).max(MIN_SIGMA);
+            changed = true;
+        }
+        if changed {
+            self.refresh();
+        }
+    }
+
+    pub fn to_value(&self) -> Value {
+        serde_json::to_value(ConnectorData::from(self)).expect("connector data is plain json")
+    }
+
     pub fn precompute(&mut self) {
         let len = self.config().max_seq_len();
         self.pdf.clear();
@@ -107,29 +155,66 @@ impl Connector {
     }
 }
 
+fn perturb(mutator: &str, variance: f64, rng: &mut impl Rng) -> f64 {
+    /* linear: uniform step within +-variance, standard: gaussian step with that variance */
+    match mutator {
+        "linear" => rng.gen_range(-variance..=variance),
+        _ => Normal::new(0.0, variance.sqrt())
+            .expect("invalid connector mutation variance")
+            .sample(rng),
+    }
+}
+
+/* the serialized form of a connector, pdf and cdf are rebuilt from mu and sigma */
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(tag = "objectType", rename_all = "lowercase")]
+pub(crate) enum ConnectorData {
+    Connector { mu: f64, sigma: f64 },
+}
+
+impl From<&Connector> for ConnectorData {
+    fn from(con: &Connector) -> ConnectorData {
+        ConnectorData::Connector {
+            mu: con.mu,
+            sigma: con.sigma,
+        }
+    }
+}
+
+impl TryFrom<ConnectorData> for Connector {
+    type Error = ConnectorError;
+
+    fn try_from(data: ConnectorData) -> Result<Connector, ConnectorError> {
+        /* comes without a config, attach one with set_config */
+        let ConnectorData::Connector { mu, sigma } = data;
+        if !mu.is_finite() {
+            return Err(ParseError::new(".mu", "finite number").into());
+        }
+        if !sigma.is_finite() || sigma <= 0.0 {
+            return Err(ParseError::new(".sigma", "positive number").into());
+        }
+        Ok(connector(mu, sigma, None))
+    }
+}
+
+impl Serialize for Connector {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ConnectorData::from(self).serialize(serializer)
+    }
+}
+
 pub fn from_value(
     con: &Value,
     config: Option<&ConnectorConfig>,
 ) -> Result<Connector, ConnectorError> {
-    let con = con.as_object().ok_or_else(|| {
-        ConnectorError::ParseJSONError(serde::de::Error::invalid_type(
-            serde::de::Unexpected::Option,
-            &"hi",
-        ))
-    })?;
-    let mu = con["mu"].as_f64().ok_or_else(|| {
-        ConnectorError::ParseJSONError(serde::de::Error::invalid_type(
-            serde::de::Unexpected::Option,
-            &"hi",
-        ))
-    })?;
-    let sigma = con["sigma"].as_f64().ok_or_else(|| {
-        ConnectorError::ParseJSONError(serde::de::Error::invalid_type(
-            serde::de::Unexpected::Option,
-            &"hi",
-        ))
-    })?;
-    Ok(connector(mu, sigma, config.cloned()))
+    let con = aux::as_object(con, "")?;
+    let data = ConnectorData::Connector {
+        mu: aux::as_f64(aux::field(con, "mu"), ".mu")?,
+        sigma: aux::as_f64(aux::field(con, "sigma"), ".sigma")?,
+    };
+    let mut con = Connector::try_from(data)?;
+    con.set_config(config.cloned());
+    Ok(con)
 }
 
 pub fn connector(mu: f64, sigma: f64, config: Option<ConnectorConfig>) -> Connector {
@@ -144,7 +229,7 @@ pub fn connector(mu: f64, sigma: f64, config: Option<ConnectorConfig>) -> Connec
             alt: Normal::new(mu, sigma).expect("invalid mu or sigma for connector"),
             pdf: Vec::with_capacity(max_seq_len * 10),
             cdf: Vec::with_capacity(max_seq_len * 10),
-            config: config,
+            config,
         };
         new.precompute();
         return new;
@@ -0,0 +1,506 @@
+use crate::config::MainConfig;
+use crate::error::DatasetError;
+use crate::shuffle;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::{fs, io, io::BufRead};
+
+const MAX_SAMPLE_ATTEMPTS: usize = 10000;
+
+#[derive(Deserialize, Serialize, Default, Debug, Clone)]
+pub struct Sequence {
+    header: String,
+    seq: String,
+}
+
+impl Sequence {
+    pub fn header(&self) -> &str {
+        &self.header
+    }
+
+    pub fn seq(&self) -> &[u8] {
+        self.seq.as_bytes()
+    }
+}
+
+#[derive(Deserialize, Serialize, Default, Debug, Clone)]
+pub struct DatasetStats {
+    file: String,
+    num_read: usize,
+    num_seqs: usize,
+    min_len: usize,
+    max_len: usize,
+    mean_len: f64,
+    gc_content: f64,
+}
+
+impl DatasetStats {
+    pub fn file(&self) -> &str {
+        &self.file
+    }
+
+    pub fn num_read(&self) -> usize {
+        self.num_read
+    }
+
+    pub fn num_seqs(&self) -> usize {
+        self.num_seqs
+    }
+
+    pub fn min_len(&self) -> usize {
+        self.min_len
+    }
+
+    pub fn max_len(&self) -> usize {
+        self.max_len
+    }
+
+    pub fn mean_len(&self) -> f64 {
+        self.mean_len
+    }
+
+    pub fn gc_content(&self) -> f64 {
+        self.gc_content
+    }
+
+    pub fn print(&self) {
+        println!(
+            "{}: {} of {} sequences, length {}-{} (mean {:.1}), GC {:.3}",
+            self.file,
+            self.num_seqs,
+            self.num_read,
+            self.min_len,
+            self.max_len,
+            self.mean_len,
+            self.gc_content
+        );
+    }
+}
+
+#[derive(Deserialize, Serialize, Default, Debug, Clone)]
+pub struct Dataset {
+    seqs: Vec<Sequence>,
+    stats: DatasetStats,
+}
+
+impl Dataset {
+    pub fn seqs(&self) -> &Vec<Sequence> {
+        &self.seqs
+    }
+
+    pub fn seq_at(&self, seq_idx: usize) -> &[u8] {
+        self.seqs[seq_idx].seq()
+    }
+
+    pub fn stats(&self) -> &DatasetStats {
+        &self.stats
+    }
+
+    pub fn len(&self) -> usize {
+        self.seqs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.seqs.is_empty()
+    }
+
+    pub fn max_seq_len(&self) -> usize {
+        self.stats.max_len
+    }
+
+    pub fn sample(&mut self, max_seqs: usize, shuffle: bool, rng: &mut impl Rng) {
+        if shuffle {
+            self.seqs.shuffle(rng);
+        }
+        self.seqs.truncate(max_seqs);
+        self.update_stats();
+    }
+
+    fn update_stats(&mut self) {
+        let mut stats = DatasetStats {
+            file: self.stats.file.clone(),
+            num_read: self.stats.num_read,
+            num_seqs: self.seqs.len(),
+            ..Default::default()
+        };
+        if self.seqs.is_empty() {
+            self.stats = stats;
+            return;
+        }
+
+        let mut total: usize = 0;
+        let mut gc: usize = 0;
+        stats.min_len = usize::MAX;
+        for seq in &self.seqs {
+            stats.min_len = stats.min_len.min(seq.seq.len());
+            stats.max_len = stats.max_len.max(seq.seq.len());
+            total += seq.seq.len();
+            gc += seq
+                .seq()
+                .iter()
+                .filter(|b| matches!(b, b'c' | b'C' | b'g' | b'G'))
+                .count();
+        }
+        stats.mean_len = total as f64 / self.seqs.len() as f64;
+        stats.gc_content = if total > 0 {
+            gc as f64 / total as f64
+        } else {
+            0.0
+        };
+        self.stats = stats;
+    }
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct Regions {
+    regions: HashMap<String, Vec<(usize, usize)>>,
+}
+
+impl Regions {
+    pub fn overlaps(&self, chrom: &str, start: usize, stop: usize) -> bool {
+        /* regions are merged and sorted, so only the last one starting before stop can overlap */
+        let regions = match self.regions.get(chrom) {
+            Some(regions) => regions,
+            None => return false,
+        };
+        let idx = regions.partition_point(|region| region.0 < stop);
+        idx > 0 && regions[idx - 1].1 > start
+    }
+}
+
+pub fn from_fasta(fasta_file: &str) -> Result<Dataset, DatasetError> {
+    let reader = io::BufReader::new(fs::File::open(fasta_file)?);
+    fasta_from_reader(reader, fasta_file)
+}
+
+pub(crate) fn fasta_from_reader(
+    reader: impl BufRead,
+    fasta_file: &str,
+) -> Result<Dataset, DatasetError> {
+    let mut seqs: Vec<Sequence> = Vec::new();
+
+    for (line_num, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with(';') {
+            continue;
+        }
+
+        if let Some(header) = line.strip_prefix('>') {
+            seqs.push(Sequence {
+                header: header.trim().to_string(),
+                seq: String::new(),
+            });
+            continue;
+        }
+
+        let curr = seqs.last_mut().ok_or_else(|| {
+            DatasetError::ParseFastaError(format!(
+                "{} line {} has sequence data before the first header",
+                fasta_file,
+                line_num + 1
+            ))
+        })?;
+        /* only ASCII letters are kept, so byte and char positions agree */
+        for base in line.chars() {
+            if !base.is_ascii_alphabetic() {
+                return Err(DatasetError::ParseFastaError(format!(
+                    "{} line {} contains invalid character {:?}",
+                    fasta_file,
+                    line_num + 1,
+                    base
+                )));
+            }
+            curr.seq.push(base);
+        }
+    }
+
+    let mut dataset = Dataset {
+        seqs,
+        stats: DatasetStats {
+            file: fasta_file.to_string(),
+            ..Default::default()
+        },
+    };
+    dataset.stats.num_read = dataset.seqs.len();
+    dataset.update_stats();
+    Ok(dataset)
+}
+
+pub fn generate_negatives(
+    positives: &Dataset,
+    size: usize,
+    kmer_len: usize,
+    rng: &mut impl Rng,
+) -> Result<Dataset, DatasetError> {
+    if positives.seqs.is_empty() {
+        return Err(DatasetError::EmptyDatasetError(
+            positives.stats.file.clone(),
+        ));
+    }
+
+    let mut seqs: Vec<Sequence> = Vec::with_capacity(size);
+    for i in 0..size {
+        let orig = &positives.seqs[i % positives.seqs.len()];
+        seqs.push(Sequence {
+            header: format!("shuffled_{} {}", i, orig.header),
+            seq: shuffle::kmer_shuffle(orig.seq(), kmer_len, rng)
+                .into_iter()
+                .map(char::from)
+                .collect(),
+        });
+    }
+
+    let mut dataset = Dataset {
+        seqs,
+        stats: DatasetStats {
+            file: format!("{} ({}-mer shuffled)", positives.stats.file, kmer_len),
+            num_read: size,
+            ..Default::default()
+        },
+    };
+    dataset.update_stats();
+    Ok(dataset)
+}
+
+pub fn positives_from_config(
+    conf: &MainConfig,
+    rng: &mut impl Rng,
+) -> Result<Dataset, DatasetError> {
+    let path = Path::new(conf.dataset_base_path_dir()).join(conf.positive_filename());
+    let mut dataset = from_fasta(&path.to_string_lossy())?;
+    dataset.sample(
+        conf.max_sequences_to_fit_pos(),
+        conf.random_shuffle_sampling_pos(),
+        rng,
+    );
+    Ok(dataset)
+}
+
+pub fn negatives_from_config(
+    conf: &MainConfig,
+    positives: &Dataset,
+    rng: &mut impl Rng,
+) -> Result<Dataset, DatasetError> {
+    let mut dataset = match conf.negative_filename() {
+        "" => generate_negatives(
+            positives,
+            conf.generated_neg_set_size(),
+            conf.generated_neg_set_kmer_len(),
+            rng,
+        )?,
+        negative_filename => {
+            let path = Path::new(conf.dataset_base_path_dir()).join(negative_filename);
+            from_fasta(&path.to_string_lossy())?
+        }
+    };
+    dataset.sample(
+        conf.max_sequences_to_fit_neg(),
+        conf.random_shuffle_sampling_neg(),
+        rng,
+    );
+    Ok(dataset)
+}
+
+pub fn regions_from_bed(bed_file: &str) -> Result<Regions, DatasetError> {
+    let reader = io::BufReader::new(fs::File::open(bed_file)?);
+    regions_from_reader(reader, bed_file)
+}
+
+fn regions_from_reader(reader: impl BufRead, bed_file: &str) -> Result<Regions, DatasetError> {
+    let mut regions: HashMap<String, Vec<(usize, usize)>> = HashMap::new();
+
+    for (line_num, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty()
+            || line.starts_with('#')
+            || line.starts_with("track")
+            || line.starts_with("browser")
+        {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let bounds: Option<(usize, usize)> = match fields.len() {
+            0..=2 => None,
+            _ => fields[1].parse().ok().zip(fields[2].parse().ok()),
+        };
+        let (start, stop) = bounds.ok_or_else(|| {
+            DatasetError::ParseBedError(format!(
+                "{} line {} is not a valid BED record",
+                bed_file,
+                line_num + 1
+            ))
+        })?;
+        regions
+            .entry(fields[0].to_string())
+            .or_default()
+            .push((start, stop));
+    }
+
+    for chrom_regions in regions.values_mut() {
+        chrom_regions.sort_unstable();
+        let mut merged: Vec<(usize, usize)> = Vec::with_capacity(chrom_regions.len());
+        for region in chrom_regions.iter() {
+            match merged.last_mut() {
+                Some(last) if region.0 <= last.1 => last.1 = last.1.max(region.1),
+                _ => merged.push(*region),
+            }
+        }
+        *chrom_regions = merged;
+    }
+    Ok(Regions { regions })
+}
+
+pub fn sample_genome(
+    genome: &Dataset,
+    positives: &Dataset,
+    size: usize,
+    excluded: Option<&Regions>,
+    max_n_frac: Option<f64>,
+    rng: &mut impl Rng,
+) -> Result<Dataset, DatasetError> {
+    /* draws fragments uniformly over valid start positions by rejection, matching the */
+    /* length of each positive sequence in turn */
+    if positives.seqs.is_empty() {
+        return Err(DatasetError::EmptyDatasetError(
+            positives.stats.file.clone(),
+        ));
+    }
+
+    let mut offsets: Vec<usize> = Vec::with_capacity(genome.seqs.len());
+    let mut total: usize = 0;
+    for chrom in &genome.seqs {
+        offsets.push(total);
+        total += chrom.seq.len();
+    }
+    if total == 0 {
+        return Err(DatasetError::EmptyDatasetError(genome.stats.file.clone()));
+    }
+
+    let mut seqs: Vec<Sequence> = Vec::with_capacity(size);
+    for i in 0..size {
+        let frag_len = positives.seqs[i % positives.seqs.len()].seq.len();
+        let mut attempts: usize = 0;
+        let frag = loop {
+            attempts += 1;
+            if attempts > MAX_SAMPLE_ATTEMPTS {
+                return Err(DatasetError::SampleGenomeError(format!(
+                    "no valid fragment of length {} found after {} attempts",
+                    frag_len, MAX_SAMPLE_ATTEMPTS
+                )));
+            }
+
+            let pos = rng.gen_range(0..total);
+            let chrom_idx = offsets.partition_point(|offset| *offset <= pos) - 1;
+            let chrom = &genome.seqs[chrom_idx];
+            let start = pos - offsets[chrom_idx];
+            let stop = start + frag_len;
+            if stop > chrom.seq.len() {
+                continue;
+            }
+
+            let name = chrom.header.split_whitespace().next().unwrap_or_default();
+            if excluded.is_some_and(|regions| regions.overlaps(name, start, stop)) {
+                continue;
+            }
+
+            let frag = &chrom.seq()[start..stop];
+            if let Some(max_n_frac) = max_n_frac {
+                let num_n = frag.iter().filter(|b| !b"acgtACGT".contains(b)).count();
+                if num_n as f64 > max_n_frac * frag_len as f64 {
+                    continue;
+                }
+            }
+            break Sequence {
+                header: format!("{}:{}-{}", name, start, stop),
+                seq: chrom.seq[start..stop].to_string(),
+            };
+        };
+        seqs.push(frag);
+    }
+
+    let mut dataset = Dataset {
+        seqs,
+        stats: DatasetStats {
+            file: format!("{} (sampled)", genome.stats.file),
+            num_read: size,
+            ..Default::default()
+        },
+    };
+    dataset.update_stats();
+    Ok(dataset)
+}
+
+/* positives shared by the population and evolution tests */
+#[cfg(test)]
+pub(crate) const TEST_POSITIVES: &str = ">p1\nacgtacgtggcatgcaacgtacgtggcatgca\n\
+    >p2\ntttacgtacgaacgtcatgcatgcaaacgtcg\n\
+    >p3\nggggacgtacgtccccacgtaaaaacgtttgc\n\
+    >p4\nacgtgcatgcaacgtacgtttgcaacgtacga\n";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha12Rng;
+
+    #[test]
+    fn parses_fasta() {
+        let text = ">one first\nACGT\nac\n;comment\n\n>two\nggcc\n";
+        let dataset = fasta_from_reader(text.as_bytes(), "test.fa").unwrap();
+        assert_eq!(dataset.len(), 2);
+        assert_eq!(dataset.seqs()[0].header(), "one first");
+        assert_eq!(dataset.seq_at(0), b"ACGTac");
+        assert_eq!(dataset.seq_at(1), b"ggcc");
+        assert_eq!(dataset.stats().min_len(), 4);
+        assert_eq!(dataset.stats().max_len(), 6);
+        assert_eq!(dataset.stats().gc_content(), 0.7);
+        assert!(fasta_from_reader("ACGT\n".as_bytes(), "test.fa").is_err());
+        assert!(fasta_from_reader(">one\nAC-GT\n".as_bytes(), "test.fa").is_err());
+    }
+
+    #[test]
+    fn parses_bed() {
+        let text = "track name=test\n# comment\nchr1\t10\t20\nchr1 15 30 peak\n\
+            chr1 40 50\nchr2 0 5\n";
+        let regions = regions_from_reader(text.as_bytes(), "test.bed").unwrap();
+        /* the first two chr1 records merge into 10-30 */
+        assert!(regions.overlaps("chr1", 25, 35));
+        assert!(regions.overlaps("chr1", 0, 11));
+        assert!(!regions.overlaps("chr1", 30, 40));
+        assert!(regions.overlaps("chr1", 45, 46));
+        assert!(!regions.overlaps("chr2", 5, 10));
+        assert!(!regions.overlaps("chr3", 0, 100));
+        assert!(regions_from_reader("chr1 10\n".as_bytes(), "test.bed").is_err());
+        assert!(regions_from_reader("chr1 a 20\n".as_bytes(), "test.bed").is_err());
+    }
+
+    #[test]
+    fn samples_genome_outside_excluded_regions() {
+        let genome = fasta_from_reader(">chr1\nACGTACGTACGTNNNNNNNN\n".as_bytes(), "g.fa").unwrap();
+        let positives = fasta_from_reader(">p\nACGT\n".as_bytes(), "p.fa").unwrap();
+        let excluded = regions_from_reader("chr1 0 6\n".as_bytes(), "x.bed").unwrap();
+        let mut rng = ChaCha12Rng::seed_from_u64(1);
+        let sampled = sample_genome(
+            &genome,
+            &positives,
+            20,
+            Some(&excluded),
+            Some(0.0),
+            &mut rng,
+        )
+        .unwrap();
+        assert_eq!(sampled.len(), 20);
+        for seq in sampled.seqs() {
+            let (start, stop) = seq.header()[5..].split_once('-').unwrap();
+            let (start, stop): (usize, usize) = (start.parse().unwrap(), stop.parse().unwrap());
+            assert!(start >= 6 && stop <= 12);
+            assert_eq!(seq.seq(), &genome.seq_at(0)[start..stop]);
+        }
+    }
+}
@@ -1,11 +1,102 @@
+use std::fmt;
 use thiserror;
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    org: Option<usize>,
+    node: Option<usize>,
+    path: String,
+    expected: String,
+}
+
+impl ParseError {
+    pub fn new(path: &str, expected: &str) -> ParseError {
+        ParseError {
+            org: None,
+            node: None,
+            path: path.to_string(),
+            expected: expected.to_string(),
+        }
+    }
+
+    pub fn org(&self) -> Option<usize> {
+        self.org
+    }
+
+    pub fn node(&self) -> Option<usize> {
+        self.node
+    }
+
+    pub fn expected(&self) -> &str {
+        &self.expected
+    }
+
+    pub fn path(&self) -> String {
+        /* [org][node] prefixes, then the path inside the node, e.g. [0][3].pwm[2].g */
+        let mut path = String::new();
+        if let Some(org) = self.org {
+            path.push_str(&format!("[{}]", org));
+        }
+        if let Some(node) = self.node {
+            path.push_str(&format!("[{}]", node));
+        }
+        path.push_str(&self.path);
+        path
+    }
+
+    pub fn at_node(mut self, node: usize) -> ParseError {
+        self.node = Some(node);
+        self
+    }
+
+    pub fn at_org(mut self, org: usize) -> ParseError {
+        self.org = Some(org);
+        self
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(org) = self.org {
+            write!(f, "organism {}, ", org)?;
+        }
+        if let Some(node) = self.node {
+            write!(f, "node {}, ", node)?;
+        }
+        let path = self.path();
+        if !path.is_empty() {
+            write!(f, "{}: ", path)?;
+        }
+        write!(f, "expected {}", self.expected)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
 #[derive(thiserror::Error, Debug)]
 pub enum OrganismError {
     #[error("failed to load organism")]
     LoadOrganismError,
+    #[error("failed to parse organism: {0}")]
+    ParseError(#[from] ParseError),
     #[error("length of recognizers exceeded length of sequence")]
     ExceedSeqError,
+    #[error("unknown energy threshold method {0}")]
+    UnknownThresholdMethodError(String),
+    #[error("invalid energy threshold param {0}")]
+    ThresholdParamError(f64),
+    #[error("energy threshold method needs a background dataset")]
+    MissingBackgroundError,
+    #[error("node {0} breaks the recognizer, connector alternation")]
+    NodeOrderError(usize),
+    #[error("organism has {0} nodes, outside the bounds [{1}, {2}]")]
+    NodeBoundsError(usize, usize, usize),
+    #[error("recognizer at node {0} has no columns")]
+    EmptyRecognizerError(usize),
+    #[error("connector at node {0} needs a finite mu and a positive sigma")]
+    InvalidConnectorError(usize),
+    #[error("connector at node {0} cannot be precomputed without a max sequence length")]
+    PrecomputeError(usize),
     #[error("failed to parse organism or config from json file")]
     ParseJSONError(#[from] serde_json::Error),
     #[error("failed to open organism or config file")]
@@ -14,6 +105,28 @@ pub enum OrganismError {
     RecognizerError(#[from] RecognizerError),
     #[error("failed to parse connector object")]
     ConnectorError(#[from] ConnectorError),
+    #[error("failed to load config")]
+    ConfigError(#[from] ConfigError),
+}
+
+impl OrganismError {
+    pub fn at_node(self, node: usize) -> OrganismError {
+        match self {
+            OrganismError::ParseError(e) => OrganismError::ParseError(e.at_node(node)),
+            OrganismError::RecognizerError(RecognizerError::ParseError(e))
+            | OrganismError::ConnectorError(ConnectorError::ParseError(e)) => {
+                OrganismError::ParseError(e.at_node(node))
+            }
+            e => e,
+        }
+    }
+
+    pub fn at_org(self, org: usize) -> OrganismError {
+        match self {
+            OrganismError::ParseError(e) => OrganismError::ParseError(e.at_org(org)),
+            e => e,
+        }
+    }
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -23,6 +136,39 @@ pub enum RecognizerError {
 
     #[error("failed to parse recognizer from JSON value")]
     ParseJSONError(#[from] serde_json::Error),
+
+    #[error("failed to parse recognizer: {0}")]
+    ParseError(#[from] ParseError),
+
+    #[error("no binding sites given to build recognizer")]
+    EmptySitesError,
+
+    #[error("binding site {0} does not match the length of the first site")]
+    SiteLengthError(String),
+
+    #[error("binding site contains invalid base {0}")]
+    InvalidBaseError(char),
+
+    #[error("pseudo count must be a non-negative number, got {0}")]
+    PseudoCountError(f64),
+
+    #[error("background {0:?} must be positive and sum to 1")]
+    BackgroundError(Vec<f64>),
+
+    #[error("failed to parse motif file: {0}")]
+    ParseMotifError(String),
+
+    #[error("failed to write motif: {0}")]
+    ExportMotifError(String),
+
+    #[error("operation is not supported for {0:?} recognizers")]
+    UnsupportedFeatError(crate::recognizer::RecognizerFeat),
+
+    #[error("recognizer has no columns")]
+    EmptyMatrixError,
+
+    #[error("failed to read or write motif file")]
+    IOError(#[from] std::io::Error),
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -31,4 +177,122 @@ pub enum ConnectorError {
     LoadConnectorError,
     #[error("failed to parse connector from json file")]
     ParseJSONError(#[from] serde_json::Error),
+    #[error("failed to parse connector: {0}")]
+    ParseError(#[from] ParseError),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum DatasetError {
+    #[error("failed to open dataset file")]
+    IOError(#[from] std::io::Error),
+    #[error("failed to parse FASTA file: {0}")]
+    ParseFastaError(String),
+    #[error("dataset {0} has no sequences")]
+    EmptyDatasetError(String),
+    #[error("failed to parse BED file: {0}")]
+    ParseBedError(String),
+    #[error("failed to sample genome: {0}")]
+    SampleGenomeError(String),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum FitnessError {
+    #[error("unknown fitness function {0}")]
+    UnknownFitnessError(String),
+    #[error("unknown or invalid cumulative fit method {0}")]
+    UnknownCumulativeFitError(String),
+    #[error("fitness needs at least one positive and one negative sequence")]
+    EmptyDatasetError,
+    #[error("failed to place organism on dataset")]
+    OrganismError(#[from] OrganismError),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum EvolutionError {
+    #[error("unknown {0} method {1}")]
+    UnknownMethodError(String, String),
+    #[error("population has no organisms")]
+    EmptyPopulationError,
+    #[error("failed to parse config from json file")]
+    ParseJSONError(#[from] serde_json::Error),
+    #[error("failed to open config or checkpoint file")]
+    IOError(#[from] std::io::Error),
+    #[error("invalid checkpoint: {0}")]
+    CheckpointError(String),
+    #[error("invalid config: {0}")]
+    ConfigError(#[from] ConfigError),
+    #[error("failed to load organisms")]
+    OrganismError(#[from] OrganismError),
+    #[error("failed to load dataset")]
+    DatasetError(#[from] DatasetError),
+    #[error("failed to evaluate fitness")]
+    FitnessError(#[from] FitnessError),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum BinaryError {
+    #[error("not an organism binary file")]
+    MagicError,
+    #[error("unsupported binary format version {0}, expected at most {1}")]
+    VersionError(u16, u16),
+    #[error("failed to read or write binary file")]
+    IOError(#[from] std::io::Error),
+    #[error("failed to parse organism: {0}")]
+    ParseError(#[from] ParseError),
+    #[error("failed to convert organism")]
+    OrganismError(#[from] OrganismError),
+    #[error("failed to load config")]
+    ConfigError(#[from] ConfigError),
+}
+
+impl BinaryError {
+    pub fn at_node(self, node: usize) -> BinaryError {
+        match self {
+            BinaryError::ParseError(e) => BinaryError::ParseError(e.at_node(node)),
+            e => e,
+        }
+    }
+
+    pub fn at_org(self, org: usize) -> BinaryError {
+        match self {
+            BinaryError::ParseError(e) => BinaryError::ParseError(e.at_org(org)),
+            e => e,
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ConfigError {
+    #[error("failed to open config file")]
+    IOError(#[from] std::io::Error),
+    #[error("failed to parse config from json file")]
+    ParseJSONError(#[from] serde_json::Error),
+    #[error("failed to parse config from toml file")]
+    ParseTOMLError(#[from] toml::de::Error),
+    #[error("config variable {0} has invalid value {1}")]
+    EnvValueError(String, String),
+    #[error("{0} must be a probability in [0, 1], got {1}")]
+    ProbabilityError(String, f64),
+    #[error("{0} must not be greater than {1}")]
+    BoundsError(String, String),
+    #[error("{0} must be positive, got {1}")]
+    NonPositiveError(String, f64),
+    #[error("{0} must not be negative, got {1}")]
+    NegativeError(String, f64),
+    #[error("unknown {0} {1}")]
+    UnknownMethodError(String, String),
+}
+
+impl ConfigError {
+    pub fn key(&self) -> Option<&str> {
+        match self {
+            ConfigError::ProbabilityError(key, _)
+            | ConfigError::BoundsError(key, _)
+            | ConfigError::NonPositiveError(key, _)
+            | ConfigError::NegativeError(key, _)
+            | ConfigError::UnknownMethodError(key, _) => Some(key),
+            ConfigError::EnvValueError(key, _) => Some(key),
+            _ => None,
+        }
+    }
 }
@@ -0,0 +1,527 @@
+use crate::aux::JsonFloat;
+use crate::config::{self, Config, MainConfig};
+use crate::dataset::{self, Dataset};
+use crate::error::EvolutionError;
+use crate::factory::{self, Factory};
+use crate::fitness::{self, Fitness};
+use crate::population::{self, Population};
+use crate::selection::{self, Selection};
+use crate::Organism;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
+use std::{fs, io};
+
+const CHECKPOINT_FILENAME: &str = "checkpoint.json";
+
+pub struct Evolution {
+    conf: MainConfig,
+    factory: Factory,
+    population: Population,
+    pos: Dataset,
+    neg: Dataset,
+    fitness: Box<dyn Fitness>,
+    selection: Box<dyn Selection>,
+    rng: ChaCha12Rng,
+    iteration: usize,
+    next_id: usize,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RngState {
+    seed: [u8; 32],
+    stream: u64,
+    word_pos: String,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Checkpoint {
+    iteration: usize,
+    next_id: usize,
+    rng: RngState,
+    config: Config,
+    pos: Dataset,
+    neg: Dataset,
+    ids: Vec<usize>,
+    fitness: Vec<JsonFloat>,
+    population: Vec<Value>,
+}
+
+impl Evolution {
+    pub fn conf(&self) -> &MainConfig {
+        &self.conf
+    }
+
+    pub fn factory(&self) -> &Factory {
+        &self.factory
+    }
+
+    pub fn population(&self) -> &Population {
+        &self.population
+    }
+
+    pub fn pos(&self) -> &Dataset {
+        &self.pos
+    }
+
+    pub fn neg(&self) -> &Dataset {
+        &self.neg
+    }
+
+    pub fn iteration(&self) -> usize {
+        self.iteration
+    }
+
+    pub fn set_selection(&mut self, selection: Box<dyn Selection>) {
+        self.selection = selection;
+    }
+
+    pub fn best(&self) -> Option<(&Organism, f64)> {
+        let (org_idx, fitness) = self.population.best()?;
+        Some((self.population.org_at(org_idx), fitness))
+    }
+
+    pub fn is_done(&self) -> Result<bool, EvolutionError> {
+        match self.conf.end_while_method() {
+            "iterations" => Ok(self.iteration >= self.conf.min_iterations()),
+            "fitness" => Ok(self
+                .best()
+                .is_some_and(|(_, fitness)| fitness >= self.conf.min_fitness())),
+            method => Err(EvolutionError::UnknownMethodError(
+                "END_WHILE_METHOD".to_string(),
+                method.to_string(),
+            )),
+        }
+    }
+
+    pub fn run(&mut self) -> Result<(), EvolutionError> {
+        while !self.is_done()? {
+            self.step()?;
+            self.export()?;
+        }
+        Ok(())
+    }
+
+    pub fn export(&self) -> Result<(), EvolutionError> {
+        /* PERIODIC_POP_EXPORT writes a full checkpoint, PERIODIC_ORG_EXPORT the best organism */
+        let dir = Path::new(self.conf.result_base_path_dir());
+        let every = |period: usize| period > 0 && self.iteration.is_multiple_of(period);
+        if every(self.conf.periodic_pop_export()) {
+            fs::create_dir_all(dir)?;
+            self.checkpoint(&dir.join(CHECKPOINT_FILENAME).to_string_lossy())?;
+        }
+        if every(self.conf.periodic_org_export()) {
+            if let Some((org, _)) = self.best() {
+                fs::create_dir_all(dir)?;
+                let org_file = dir.join(format!("org_{}.json", self.iteration));
+                org.to_json(&org_file.to_string_lossy())?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn checkpoint(&self, checkpoint_file: &str) -> Result<(), EvolutionError> {
+        let mut population: Vec<Value> = Vec::with_capacity(self.population.len());
+        for org in self.population.orgs() {
+            population.push(org.to_value()?);
+        }
+        let checkpoint = Checkpoint {
+            iteration: self.iteration,
+            next_id: self.next_id,
+            rng: RngState {
+                seed: self.rng.get_seed(),
+                stream: self.rng.get_stream(),
+                word_pos: self.rng.get_word_pos().to_string(),
+            },
+            config: config::config(
+                self.conf.clone(),
+                self.factory.org_conf().clone(),
+                self.factory.conf().clone(),
+                self.factory.rec_conf().clone(),
+                self.factory.con_conf().clone(),
+            ),
+            pos: self.pos.clone(),
+            neg: self.neg.clone(),
+            ids: self.population.orgs().iter().map(|org| org.id()).collect(),
+            fitness: self
+                .population
+                .fitness()
+                .iter()
+                .map(|fitness| JsonFloat::from(*fitness))
+                .collect(),
+            population,
+        };
+
+        /* write next to the target and rename, so a killed job never leaves half a checkpoint */
+        let tmp_file = format!("{}.tmp", checkpoint_file);
+        fs::write(&tmp_file, serde_json::to_string(&checkpoint)?)?;
+        fs::rename(&tmp_file, checkpoint_file)?;
+        Ok(())
+    }
+
+    pub fn step(&mut self) -> Result<(), EvolutionError> {
+        let size = self.conf.population_length();
+        let mut next: Vec<Organism> = Vec::with_capacity(size);
+        /* elites are carried over unchanged, ids included */
+        let elites = selection::ranked(self.population.fitness());
+        for &org_idx in elites.iter().take(self.conf.elitism().min(size)) {
+            next.push(self.population.org_at(org_idx).clone());
+        }
+        while next.len() < size {
+            let parent = self.select();
+            let mut child =
+                if self.rng.gen::<f64>() < self.factory.conf().recombination_probability() {
+                    let other = self.select();
+                    self.population.org_at(parent).recombine(
+                        self.population.org_at(other),
+                        &self.factory,
+                        &mut self.rng,
+                    )
+                } else {
+                    self.population.org_at(parent).clone()
+                };
+            child.mutate(&self.factory, &mut self.rng);
+            child.set_id(self.next_id);
+            self.next_id += 1;
+            next.push(child);
+        }
+
+        self.population = population::population(next);
+        self.evaluate()?;
+        self.iteration += 1;
+        Ok(())
+    }
+
+    fn evaluate(&mut self) -> Result<(), EvolutionError> {
+        match self.conf.run_mode() {
+            "serial" => self
+                .population
+                .evaluate(&self.pos, &self.neg, self.fitness.as_ref())?,
+            "parallel" => self.population.evaluate_parallel(
+                &self.pos,
+                &self.neg,
+                self.fitness.as_ref(),
+                self.conf.num_threads(),
+            )?,
+            mode => {
+                return Err(EvolutionError::UnknownMethodError(
+                    "RUN_MODE".to_string(),
+                    mode.to_string(),
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    fn select(&mut self) -> usize {
+        self.selection
+            .select(self.population.fitness(), &mut self.rng)
+    }
+}
+
+pub fn evolution(
+    conf: MainConfig,
+    factory: Factory,
+    population: Population,
+    pos: Dataset,
+    neg: Dataset,
+    rng: ChaCha12Rng,
+) -> Result<Evolution, EvolutionError> {
+    let fitness = fitness::from_config(&conf, factory.org_conf())?;
+    let selection = selection::from_name(conf.selection_method())?;
+    let mut new = Evolution {
+        conf,
+        factory,
+        population,
+        pos,
+        neg,
+        fitness,
+        selection,
+        rng,
+        iteration: 0,
+        next_id: 0,
+    };
+    if new.population.is_empty() {
+        return Err(EvolutionError::EmptyPopulationError);
+    }
+    for org in new.population.orgs_mut() {
+        org.set_id(new.next_id);
+        new.next_id += 1;
+    }
+    new.evaluate()?;
+    Ok(new)
+}
+
+pub fn from_json(conf_file: &str) -> Result<Evolution, EvolutionError> {
+    from_config(config::load(&[conf_file])?)
+}
+
+pub fn from_config(config: Config) -> Result<Evolution, EvolutionError> {
+    config.validate()?;
+    let (mut conf, org_conf, fac_conf, rec_conf, con_conf) = config.into_parts();
+
+    /* SEED alone determines the run; without one a seed is drawn and kept in the config */
+    let seed = match conf.seed() {
+        Some(seed) => seed,
+        None => ChaCha12Rng::from_entropy().gen(),
+    };
+    conf.set_seed(seed);
+    let mut rng = ChaCha12Rng::seed_from_u64(seed);
+    let factory = factory::factory(fac_conf, org_conf, rec_conf, con_conf)?;
+    let pos = dataset::positives_from_config(&conf, &mut rng)?;
+    let neg = dataset::negatives_from_config(&conf, &pos, &mut rng)?;
+
+    let size = conf.population_length();
+    let mut population = match conf.population_origin() {
+        "random" => population::random(&factory, size, &mut rng),
+        "file" => {
+            let org_conf = config::config(
+                conf.clone(),
+                factory.org_conf().clone(),
+                factory.conf().clone(),
+                factory.rec_conf().clone(),
+                factory.con_conf().clone(),
+            );
+            let org_file = fs::File::open(conf.input_filename())?;
+            let mut orgs: Vec<Organism> = Vec::with_capacity(size);
+            for org in crate::organisms_from_reader(org_file, Some(&org_conf)).take(size) {
+                /* organism.json holds probabilities, mutation and scoring work on log-odds */
+                let mut org = org?;
+                org.to_pssm(None);
+                orgs.push(org);
+            }
+            population::population(orgs)
+        }
+        origin => {
+            return Err(EvolutionError::UnknownMethodError(
+                "POPULATION_ORIGIN".to_string(),
+                origin.to_string(),
+            ))
+        }
+    };
+    population.orgs_mut().truncate(size);
+    let num_loaded = population.len();
+    for i in num_loaded..size {
+        let org = match conf.population_fill_type() {
+            "copy" if num_loaded > 0 => population.org_at(i % num_loaded).clone(),
+            "copy" | "random" => factory.organism(&mut rng),
+            fill_type => {
+                return Err(EvolutionError::UnknownMethodError(
+                    "POPULATION_FILL_TYPE".to_string(),
+                    fill_type.to_string(),
+                ))
+            }
+        };
+        population.push(org);
+    }
+
+    evolution(conf, factory, population, pos, neg, rng)
+}
+
+pub fn resume(checkpoint_file: &str) -> Result<Evolution, EvolutionError> {
+    let checkpoint_reader = io::BufReader::new(fs::File::open(checkpoint_file)?);
+    let checkpoint: Checkpoint = serde_json::from_reader(checkpoint_reader)?;
+    checkpoint.config.validate()?;
+    let num_orgs = checkpoint.population.len();
+    if num_orgs == 0 {
+        return Err(EvolutionError::EmptyPopulationError);
+    }
+    if checkpoint.ids.len() != num_orgs || checkpoint.fitness.len() != num_orgs {
+        return Err(EvolutionError::CheckpointError(format!(
+            "{} organisms but {} ids and {} fitness values",
+            num_orgs,
+            checkpoint.ids.len(),
+            checkpoint.fitness.len()
+        )));
+    }
+
+    let mut rng = ChaCha12Rng::from_seed(checkpoint.rng.seed);
+    rng.set_stream(checkpoint.rng.stream);
+    rng.set_word_pos(checkpoint.rng.word_pos.parse().map_err(|_| {
+        EvolutionError::CheckpointError(format!(
+            "invalid rng word position {}",
+            checkpoint.rng.word_pos
+        ))
+    })?);
+
+    let mut orgs: Vec<Organism> = Vec::with_capacity(num_orgs);
+    for (i, (org, id)) in checkpoint
+        .population
+        .iter()
+        .zip(&checkpoint.ids)
+        .enumerate()
+    {
+        let mut org = crate::from_value(
+            org,
+            Some(checkpoint.config.organism()),
+            Some(checkpoint.config.recognizer()),
+            Some(checkpoint.config.connector()),
+        )
+        .map_err(|e| e.at_org(i))?;
+        org.set_id(*id);
+        orgs.push(org);
+    }
+    let mut population = population::population(orgs);
+    population.set_fitness(
+        checkpoint
+            .fitness
+            .iter()
+            .map(|fitness| {
+                fitness.value().ok_or_else(|| {
+                    EvolutionError::CheckpointError(format!("invalid fitness value {:?}", fitness))
+                })
+            })
+            .collect::<Result<Vec<f64>, EvolutionError>>()?,
+    );
+
+    let (conf, org_conf, fac_conf, rec_conf, con_conf) = checkpoint.config.into_parts();
+    let factory = factory::factory(fac_conf, org_conf, rec_conf, con_conf)?;
+    Ok(Evolution {
+        fitness: fitness::from_config(&conf, factory.org_conf())?,
+        selection: selection::from_name(conf.selection_method())?,
+        conf,
+        factory,
+        population,
+        pos: checkpoint.pos,
+        neg: checkpoint.neg,
+        rng,
+        iteration: checkpoint.iteration,
+        next_id: checkpoint.next_id,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checkpoint_fitness_keeps_non_finite_values() {
+        for fitness in [1.5, -0.25, f64::INFINITY, f64::NEG_INFINITY] {
+            let json = serde_json::to_string(&JsonFloat::from(fitness)).unwrap();
+            let read: JsonFloat = serde_json::from_str(&json).unwrap();
+            assert_eq!(read.value(), Some(fitness));
+        }
+        let json = serde_json::to_string(&JsonFloat::from(f64::NAN)).unwrap();
+        assert_eq!(json, "\"nan\"");
+        let read: JsonFloat = serde_json::from_str(&json).unwrap();
+        assert!(read.value().unwrap().is_nan());
+        let read: JsonFloat = serde_json::from_str("\"big\"").unwrap();
+        assert_eq!(read.value(), None);
+    }
+
+    fn test_config(dir: &Path, overlay: Value) -> Config {
+        fs::create_dir_all(dir).unwrap();
+        fs::write(dir.join("pos.fa"), dataset::TEST_POSITIVES).unwrap();
+        let mut conf = serde_json::to_value(Config::default()).unwrap();
+        config::merge(
+            &mut conf,
+            serde_json::json!({"main": {
+                "DATASET_BASE_PATH_DIR": dir.to_string_lossy(),
+                "POSITIVE_FILENAME": "pos.fa",
+                "GENERATED_NEG_SET_SIZE": 4,
+                "POPULATION_LENGTH": 8,
+                "SEED": 17,
+            }}),
+        );
+        config::merge(&mut conf, overlay);
+        serde_json::from_value(conf).unwrap()
+    }
+
+    #[test]
+    fn resumed_run_matches_uninterrupted_run() {
+        /* a zero pseudo count gives -inf log-odds, which the checkpoint must keep */
+        for (i, overlay) in [
+            serde_json::json!({}),
+            serde_json::json!({
+                "recognizer": {"PSEUDO_COUNT": 0.0},
+                "organismFactory": {"PWM_NUM_OF_BINDING_SITES": 2},
+            }),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let dir =
+                std::env::temp_dir().join(format!("organism_resume_{}_{}", std::process::id(), i));
+            let conf = test_config(&dir, overlay);
+
+            let mut uninterrupted = from_config(conf.clone()).unwrap();
+            for _ in 0..4 {
+                uninterrupted.step().unwrap();
+            }
+
+            let mut interrupted = from_config(conf).unwrap();
+            for _ in 0..2 {
+                interrupted.step().unwrap();
+            }
+            let non_finite = interrupted.population().orgs().iter().any(|org| {
+                (0..org.num_recs()).any(|j| org.rec_at(j).matrix().iter().any(|v| !v.is_finite()))
+            });
+            assert_eq!(non_finite, i == 1);
+            let checkpoint_file = dir.join(CHECKPOINT_FILENAME);
+            interrupted
+                .checkpoint(&checkpoint_file.to_string_lossy())
+                .unwrap();
+            let mut resumed = resume(&checkpoint_file.to_string_lossy()).unwrap();
+            for _ in 0..2 {
+                resumed.step().unwrap();
+            }
+            fs::remove_dir_all(&dir).unwrap();
+
+            assert_eq!(resumed.iteration(), uninterrupted.iteration());
+            let bits = |evolution: &Evolution| -> Vec<u64> {
+                let fitness = evolution.population().fitness();
+                fitness.iter().map(|fitness| fitness.to_bits()).collect()
+            };
+            assert_eq!(bits(&resumed), bits(&uninterrupted));
+            for (org, expected) in resumed
+                .population()
+                .orgs()
+                .iter()
+                .zip(uninterrupted.population().orgs())
+            {
+                assert_eq!(org.id(), expected.id());
+                assert_eq!(org.to_value().unwrap(), expected.to_value().unwrap());
+            }
+            assert_eq!(resumed.rng.gen::<u64>(), uninterrupted.rng.gen::<u64>());
+        }
+    }
+
+    #[test]
+    fn file_origin_converts_organisms_to_log_odds() {
+        let dir = std::env::temp_dir().join(format!("organism_file_{}", std::process::id()));
+        let org_file = dir.join("organisms.json");
+        let conf = test_config(
+            &dir,
+            serde_json::json!({"main": {
+                "POPULATION_ORIGIN": "file",
+                "INPUT_FILENAME": org_file.to_string_lossy(),
+                "POPULATION_LENGTH": 3,
+            }}),
+        );
+        let org = serde_json::json!([
+            {"objectType": "pssm", "pwm": [
+                {"a": 0.7, "c": 0.1, "g": 0.1, "t": 0.1},
+                {"a": 0.1, "c": 0.1, "g": 0.1, "t": 0.7}
+            ]},
+            {"objectType": "connector", "mu": 4.0, "sigma": 1.0},
+            {"objectType": "pssm", "pwm": [{"a": 0.1, "c": 0.7, "g": 0.1, "t": 0.1}]}
+        ]);
+        fs::write(&org_file, serde_json::json!([org, org]).to_string()).unwrap();
+
+        let evolution = from_config(conf).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let orgs = evolution.population().orgs();
+        assert_eq!(orgs.len(), 3);
+        let first = orgs[0].rec_at(0);
+        assert!(first.log_odds());
+        assert!((first.matrix()[0] - (0.7_f64 / 0.25).log2()).abs() < 1e-12);
+        assert!(orgs
+            .iter()
+            .all(|org| (0..org.num_recs()).all(|j| org.rec_at(j).log_odds())));
+    }
+}
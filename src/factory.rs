@@ -0,0 +1,140 @@
+use crate::config::{self, ConnectorConfig, FactoryConfig, OrganismConfig, RecognizerConfig};
+use crate::connector::{self, Connector};
+use crate::error::ConfigError;
+use crate::recognizer::{self, Recognizer};
+use crate::{organism, Organism};
+use rand::distributions::Distribution;
+use rand::Rng;
+use statrs::distribution::Poisson;
+
+#[derive(Debug, Clone)]
+pub struct Factory {
+    conf: FactoryConfig,
+    org_conf: OrganismConfig,
+    rec_conf: RecognizerConfig,
+    con_conf: ConnectorConfig,
+    num_recs: Poisson,
+}
+
+impl Factory {
+    pub fn conf(&self) -> &FactoryConfig {
+        &self.conf
+    }
+
+    pub fn org_conf(&self) -> &OrganismConfig {
+        &self.org_conf
+    }
+
+    pub fn rec_conf(&self) -> &RecognizerConfig {
+        &self.rec_conf
+    }
+
+    pub fn con_conf(&self) -> &ConnectorConfig {
+        &self.con_conf
+    }
+
+    pub fn recognizer(&self, rng: &mut impl Rng) -> Recognizer {
+        /* log-odds profile of PWM_NUM_OF_BINDING_SITES random sites, as a fresh motif */
+        /* would be */
+        let num_sites = self.conf.pwm_num_of_binding_sites().max(1);
+        let mut counts: Vec<f64> = vec![0.0; self.conf.pwm_length() * 4];
+        for _ in 0..num_sites {
+            for col in counts.chunks_mut(4) {
+                col[rng.gen_range(0..4)] += 1.0;
+            }
+        }
+        let mut rec = recognizer::from_counts(&counts, num_sites, self.rec_conf.pseudo_count());
+        rec.to_pssm(None);
+        rec.set_config(Some(self.rec_conf.clone()));
+        rec
+    }
+
+    pub fn connector(&self, rng: &mut impl Rng) -> Connector {
+        let mu = rng.gen_range(self.conf.min_mu()..=self.conf.max_mu());
+        let sigma = rng.gen_range(self.conf.min_sigma()..=self.conf.max_sigma());
+        connector::connector(mu, sigma, Some(self.con_conf.clone()))
+    }
+
+    pub fn organism(&self, rng: &mut impl Rng) -> Organism {
+        let min_recs = (self.org_conf.min_nodes() + 2) / 2;
+        let max_recs = self.org_conf.max_nodes().div_ceil(2).max(min_recs).max(1);
+        let num_recs = (self.num_recs.sample(rng) as usize).clamp(min_recs.max(1), max_recs);
+
+        let mut recs: Vec<Recognizer> = Vec::with_capacity(num_recs);
+        let mut cons: Vec<Connector> = Vec::with_capacity(num_recs - 1);
+        for i in 0..num_recs {
+            recs.push(self.recognizer(rng));
+            if i > 0 {
+                cons.push(self.connector(rng));
+            }
+        }
+        organism(recs, cons, None, Some(self.org_conf.clone()))
+    }
+}
+
+pub fn factory(
+    conf: FactoryConfig,
+    org_conf: OrganismConfig,
+    rec_conf: RecognizerConfig,
+    con_conf: ConnectorConfig,
+) -> Result<Factory, ConfigError> {
+    /* the settings used here are checked again, so recognizer, connector and organism */
+    /* never see an invalid config even when Config::validate was skipped */
+    config::validate_factory(&conf)?;
+    config::non_negative("recognizer.PSEUDO_COUNT", rec_conf.pseudo_count())?;
+    let lambda = conf.num_recognizers_lambda_param();
+    let num_recs = Poisson::new(lambda).map_err(|_| {
+        ConfigError::NonPositiveError(
+            "organismFactory.NUM_RECOGNIZERS_LAMBDA_PARAM".to_string(),
+            lambda,
+        )
+    })?;
+    Ok(Factory {
+        conf,
+        org_conf,
+        rec_conf,
+        con_conf,
+        num_recs,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn factory_with(overlay: serde_json::Value) -> Result<Factory, ConfigError> {
+        let mut conf = serde_json::to_value(FactoryConfig::default()).unwrap();
+        config::merge(&mut conf, overlay);
+        factory(
+            serde_json::from_value(conf).unwrap(),
+            OrganismConfig::default(),
+            RecognizerConfig::default(),
+            ConnectorConfig::default(),
+        )
+    }
+
+    #[test]
+    fn rejects_configs_that_skipped_validate() {
+        assert!(factory_with(json!({})).is_ok());
+        for (overlay, key) in [
+            (
+                json!({"MIN_MU": 90.0, "MAX_MU": 10.0}),
+                "organismFactory.MIN_MU",
+            ),
+            (json!({"MIN_SIGMA": 0.0}), "organismFactory.MIN_SIGMA"),
+            (
+                json!({"MIN_SIGMA": 5.0, "MAX_SIGMA": 1.0}),
+                "organismFactory.MIN_SIGMA",
+            ),
+            (json!({"PWM_LENGTH": 0}), "organismFactory.PWM_LENGTH"),
+            (
+                json!({"NUM_RECOGNIZERS_LAMBDA_PARAM": -1.0}),
+                "organismFactory.NUM_RECOGNIZERS_LAMBDA_PARAM",
+            ),
+        ] {
+            let e = factory_with(overlay).unwrap_err();
+            assert_eq!(e.key(), Some(key));
+        }
+    }
+}
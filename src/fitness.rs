@@ -0,0 +1,257 @@
+use crate::config::{MainConfig, OrganismConfig};
+use crate::dataset::Dataset;
+use crate::error::{FitnessError, OrganismError};
+use crate::Organism;
+use statrs::statistics::Statistics;
+
+pub trait Fitness: Sync {
+    fn score(&self, pos: &[f64], neg: &[f64]) -> f64;
+}
+
+#[derive(Default, Copy, Clone, Debug)]
+pub struct Welchs;
+
+#[derive(Default, Copy, Clone, Debug)]
+pub struct MeanDifference;
+
+#[derive(Default, Copy, Clone, Debug)]
+pub struct MannWhitney;
+
+#[derive(Default, Copy, Clone, Debug)]
+pub struct Auroc;
+
+#[derive(Copy, Clone, Debug)]
+pub struct Boltzmann {
+    temperature: f64,
+}
+
+impl Boltzmann {
+    pub fn new(temperature: f64) -> Boltzmann {
+        Boltzmann { temperature }
+    }
+
+    pub fn temperature(&self) -> f64 {
+        self.temperature
+    }
+}
+
+impl Default for Boltzmann {
+    fn default() -> Boltzmann {
+        Boltzmann { temperature: 1.0 }
+    }
+}
+
+#[derive(Default, Copy, Clone, Debug)]
+pub enum CumulativeFit {
+    #[default]
+    Mean,
+    Median,
+    Sum,
+    TrimmedMean(f64),
+    TopMean(usize),
+    LogSumExp,
+}
+
+impl CumulativeFit {
+    pub fn from_name(name: &str) -> Result<CumulativeFit, FitnessError> {
+        /* parameterised methods take their parameter after a colon, e.g. "top_k_mean:5" */
+        let (method, param) = match name.split_once(':') {
+            Some((method, param)) => (method, Some(param)),
+            None => (name, None),
+        };
+        let invalid = || FitnessError::UnknownCumulativeFitError(name.to_string());
+        match (method, param) {
+            ("mean", None) => Ok(CumulativeFit::Mean),
+            ("median", None) => Ok(CumulativeFit::Median),
+            ("sum", None) => Ok(CumulativeFit::Sum),
+            ("log_sum_exp", None) => Ok(CumulativeFit::LogSumExp),
+            ("trimmed_mean", None) => Ok(CumulativeFit::TrimmedMean(0.1)),
+            ("trimmed_mean", Some(param)) => match param.parse::<f64>() {
+                Ok(frac) if (0.0..0.5).contains(&frac) => Ok(CumulativeFit::TrimmedMean(frac)),
+                _ => Err(invalid()),
+            },
+            ("top_k_mean", None) => Ok(CumulativeFit::TopMean(1)),
+            ("top_k_mean", Some(param)) => match param.parse::<usize>() {
+                Ok(k) if k > 0 => Ok(CumulativeFit::TopMean(k)),
+                _ => Err(invalid()),
+            },
+            _ => Err(invalid()),
+        }
+    }
+
+    pub fn aggregate(&self, energies: &[f64]) -> f64 {
+        if energies.is_empty() {
+            return f64::NAN;
+        }
+        match self {
+            CumulativeFit::Mean => energies.mean(),
+            CumulativeFit::Sum => energies.iter().sum(),
+            CumulativeFit::LogSumExp => log_mean_exp(energies, 1.0) + (energies.len() as f64).ln(),
+            CumulativeFit::Median => {
+                let sorted = sorted(energies);
+                let mid = sorted.len() / 2;
+                match sorted.len() % 2 {
+                    0 => (sorted[mid - 1] + sorted[mid]) / 2.0,
+                    _ => sorted[mid],
+                }
+            }
+            CumulativeFit::TrimmedMean(frac) => {
+                let sorted = sorted(energies);
+                let cut = (frac * sorted.len() as f64).floor() as usize;
+                sorted[cut..sorted.len() - cut].mean()
+            }
+            CumulativeFit::TopMean(k) => {
+                let sorted = sorted(energies);
+                sorted[sorted.len() - (*k).min(sorted.len())..].mean()
+            }
+        }
+    }
+}
+
+impl Fitness for CumulativeFit {
+    fn score(&self, pos: &[f64], neg: &[f64]) -> f64 {
+        self.aggregate(pos) - self.aggregate(neg)
+    }
+}
+
+impl Fitness for Welchs {
+    fn score(&self, pos: &[f64], neg: &[f64]) -> f64 {
+        let diff = pos.mean() - neg.mean();
+        let se = (pos.variance() / pos.len() as f64 + neg.variance() / neg.len() as f64).sqrt();
+        let t = diff / se;
+        if t.is_nan() {
+            return 0.0;
+        }
+        t
+    }
+}
+
+impl Fitness for MeanDifference {
+    fn score(&self, pos: &[f64], neg: &[f64]) -> f64 {
+        pos.mean() - neg.mean()
+    }
+}
+
+impl Fitness for MannWhitney {
+    fn score(&self, pos: &[f64], neg: &[f64]) -> f64 {
+        mann_whitney_u(pos, neg)
+    }
+}
+
+impl Fitness for Auroc {
+    fn score(&self, pos: &[f64], neg: &[f64]) -> f64 {
+        mann_whitney_u(pos, neg) / (pos.len() * neg.len()) as f64
+    }
+}
+
+impl Fitness for Boltzmann {
+    fn score(&self, pos: &[f64], neg: &[f64]) -> f64 {
+        /* share of the mean Boltzmann weight e^(E/T) that falls on the positive set */
+        let log_pos = log_mean_exp(pos, self.temperature);
+        let log_neg = log_mean_exp(neg, self.temperature);
+        1.0 / (1.0 + (log_neg - log_pos).exp())
+    }
+}
+
+pub fn from_name(name: &str) -> Result<Box<dyn Fitness>, FitnessError> {
+    match name {
+        "welchs" => Ok(Box::new(Welchs)),
+        "mean_difference" => Ok(Box::new(MeanDifference)),
+        "mann_whitney" => Ok(Box::new(MannWhitney)),
+        "auroc" => Ok(Box::new(Auroc)),
+        "boltzmann" => Ok(Box::new(Boltzmann::default())),
+        _ => Err(FitnessError::UnknownFitnessError(name.to_string())),
+    }
+}
+
+pub fn from_config(
+    conf: &MainConfig,
+    org_conf: &OrganismConfig,
+) -> Result<Box<dyn Fitness>, FitnessError> {
+    match conf.fitness_function() {
+        "cumulative" => Ok(Box::new(CumulativeFit::from_name(
+            org_conf.cumulative_fit_method(),
+        )?)),
+        name => from_name(name),
+    }
+}
+
+pub fn cumulative_fit(
+    org: &mut Organism,
+    dataset: &Dataset,
+    method: &CumulativeFit,
+) -> Result<f64, FitnessError> {
+    Ok(method.aggregate(&energies(org, dataset)?))
+}
+
+pub fn energies(org: &mut Organism, dataset: &Dataset) -> Result<Vec<f64>, OrganismError> {
+    if dataset.is_empty() {
+        return Ok(Vec::new());
+    }
+    if org.len_recs() > dataset.stats().min_len() {
+        return Err(OrganismError::ExceedSeqError);
+    }
+    org.check(dataset.max_seq_len())?;
+
+    let mut energies: Vec<f64> = Vec::with_capacity(dataset.len());
+    for seq in dataset.seqs() {
+        energies.push(org.place(seq.seq(), None).energy());
+    }
+    Ok(energies)
+}
+
+pub fn evaluate(
+    org: &mut Organism,
+    pos: &Dataset,
+    neg: &Dataset,
+    fitness: &dyn Fitness,
+) -> Result<f64, FitnessError> {
+    if pos.is_empty() || neg.is_empty() {
+        return Err(FitnessError::EmptyDatasetError);
+    }
+    let pos_energies = energies(org, pos)?;
+    let neg_energies = energies(org, neg)?;
+    Ok(fitness.score(&pos_energies, &neg_energies))
+}
+
+fn mann_whitney_u(pos: &[f64], neg: &[f64]) -> f64 {
+    /* U for the positives, from mid-ranks of the pooled energies so ties count half */
+    let mut pooled: Vec<(f64, bool)> = pos
+        .iter()
+        .map(|e| (*e, true))
+        .chain(neg.iter().map(|e| (*e, false)))
+        .collect();
+    pooled.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut rank_sum: f64 = 0.0;
+    let mut i: usize = 0;
+    while i < pooled.len() {
+        let mut j = i;
+        while j < pooled.len() && pooled[j].0 == pooled[i].0 {
+            j += 1;
+        }
+        let mid_rank = (i + j + 1) as f64 / 2.0;
+        rank_sum += mid_rank * pooled[i..j].iter().filter(|e| e.1).count() as f64;
+        i = j;
+    }
+
+    let n_pos = pos.len() as f64;
+    rank_sum - n_pos * (n_pos + 1.0) / 2.0
+}
+
+fn sorted(energies: &[f64]) -> Vec<f64> {
+    let mut sorted = energies.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    sorted
+}
+
+fn log_mean_exp(energies: &[f64], temperature: f64) -> f64 {
+    let max = energies
+        .iter()
+        .fold(f64::NEG_INFINITY, |a, b| a.max(*b / temperature));
+    if !max.is_finite() {
+        return max;
+    }
+    let sum: f64 = energies.iter().map(|e| (e / temperature - max).exp()).sum();
+    max + (sum / energies.len() as f64).ln()
+}
@@ -1,32 +1,65 @@
 mod aux;
-mod config;
-mod connector;
-mod error;
+pub mod binary;
+pub mod builder;
+pub mod config;
+pub mod connector;
+pub mod dataset;
+pub mod error;
+pub mod evolution;
+pub mod factory;
+pub mod fitness;
+pub mod motif;
 mod placement;
-mod recognizer;
-use crate::config::{ConnectorConfig, OrganismConfig, RecognizerConfig};
-use crate::connector::Connector;
-use crate::error::OrganismError;
+pub mod population;
+pub mod recognizer;
+pub mod selection;
+pub mod shuffle;
+pub mod similarity;
+use crate::builder::OrganismBuilder;
+use crate::config::{Config, ConnectorConfig, OrganismConfig, RecognizerConfig};
+use crate::connector::{Connector, ConnectorData};
+use crate::dataset::Dataset;
+use crate::error::{OrganismError, ParseError};
+use crate::factory::Factory;
 use crate::placement::Placement;
-use crate::recognizer::Recognizer;
-use rand::random;
+use crate::recognizer::{Recognizer, RecognizerData, RecognizerFeat};
+use rand::Rng;
+use serde::{ser, Deserialize, Serialize, Serializer};
 use serde_json::Value;
-use std::{cell, f64, fs, io, mem};
+use std::io::{BufRead, BufReader, Read};
+use std::{f64, fs, io, mem};
 
-#[derive(Clone, Debug, Default)]
+/* version 1 is the bare node array of organism.json, version 2 the envelope */
+const ORGANISM_VERSION: usize = 2;
+
+struct OrganismReader<R: BufRead> {
+    reader: R,
+    config: Option<Config>,
+    org_idx: usize,
+    started: bool,
+    done: bool,
+}
+
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(try_from = "OrganismData")]
 pub struct Organism {
-    recs: Vec<cell::RefCell<Recognizer>>,
-    cons: Vec<cell::RefCell<Connector>>,
+    recs: Vec<Recognizer>,
+    cons: Vec<Connector>,
     id: Option<usize>,
     config: Option<OrganismConfig>,
+    threshold: Option<f64>,
 }
 
 impl Organism {
-    pub fn recs(&self) -> &Vec<cell::RefCell<Recognizer>> {
+    pub fn builder() -> OrganismBuilder {
+        OrganismBuilder::default()
+    }
+
+    pub fn recs(&self) -> &Vec<Recognizer> {
         &self.recs
     }
 
-    pub fn cons(&self) -> &Vec<cell::RefCell<Connector>> {
+    pub fn cons(&self) -> &Vec<Connector> {
         &self.cons
     }
 
@@ -34,16 +67,51 @@ impl Organism {
         self.id.expect("organism does not have an id")
     }
 
+    pub fn has_id(&self) -> bool {
+        self.id.is_some()
+    }
+
     pub fn config(&self) -> &OrganismConfig {
         self.config
             .as_ref()
             .expect("organism does not have a config")
     }
 
+    pub fn set_config(&mut self, config: &Config) {
+        self.config = Some(config.organism().clone());
+        for rec in self.recs.iter_mut() {
+            rec.set_config(Some(config.recognizer().clone()));
+        }
+        for con in self.cons.iter_mut() {
+            con.set_config(Some(config.connector().clone()));
+        }
+        self.threshold = None;
+    }
+
+    pub fn set_id(&mut self, id: usize) {
+        self.id = Some(id);
+    }
+
+    pub fn threshold(&self) -> Option<f64> {
+        self.threshold
+    }
+
+    pub fn set_threshold(&mut self, threshold: f64) {
+        self.threshold = Some(threshold);
+    }
+
+    pub fn clear_threshold(&mut self) {
+        self.threshold = None;
+    }
+
     pub fn len(&self) -> usize {
         self.recs.len() + self.cons.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.recs.is_empty()
+    }
+
     pub fn num_recs(&self) -> usize {
         self.recs.len()
     }
@@ -55,41 +123,46 @@ impl Organism {
     pub fn len_recs(&self) -> usize {
         let mut sum: usize = 0;
         for i in 0..self.recs.len() {
-            sum += &self.recs[i].borrow().len()
+            sum += self.recs[i].len()
         }
         sum
     }
 
-    pub fn rec_at(&self, rec_idx: usize) -> &cell::RefCell<recognizer::Recognizer> {
+    pub fn rec_at(&self, rec_idx: usize) -> &Recognizer {
         &self.recs[rec_idx]
     }
 
-    pub fn rec_at_mut(&mut self, rec_idx: usize) -> &mut cell::RefCell<recognizer::Recognizer> {
+    pub fn rec_at_mut(&mut self, rec_idx: usize) -> &mut Recognizer {
+        self.threshold = None;
         &mut self.recs[rec_idx]
     }
 
-    pub fn con_at(&self, con_idx: usize) -> &cell::RefCell<connector::Connector> {
+    pub fn con_at(&self, con_idx: usize) -> &Connector {
         &self.cons[con_idx]
     }
 
-    pub fn con_at_mut(&mut self, con_idx: usize) -> &mut cell::RefCell<connector::Connector> {
+    pub fn con_at_mut(&mut self, con_idx: usize) -> &mut Connector {
+        self.threshold = None;
         &mut self.cons[con_idx]
     }
 
     pub fn swap_rec(&mut self, rec_a: usize, rec_b: usize) {
         self.recs.swap(rec_a, rec_b);
+        self.threshold = None;
     }
 
     pub fn swap_con(&mut self, con_a: usize, con_b: usize) {
-        self.recs.swap(con_a, con_b);
+        self.cons.swap(con_a, con_b);
+        self.threshold = None;
     }
 
-    pub fn remove(&mut self, rec_idx: usize) {
+    pub fn remove(&mut self, rec_idx: usize, rng: &mut impl Rng) {
         let num_recs = self.num_recs();
         let num_cons = self.num_cons();
         if num_recs < 2 {
             return;
         }
+        self.threshold = None;
 
         let con_idx;
         let adj_idx;
@@ -101,43 +174,138 @@ impl Organism {
 
             _ if rec_idx == num_cons => {
                 con_idx = num_recs - 2;
-                adj_idx = con_idx - 1
+                adj_idx = con_idx.saturating_sub(1)
             }
 
             _ => {
-                con_idx = if random() { rec_idx - 1 } else { rec_idx };
-                adj_idx = con_idx
+                con_idx = if rng.gen() { rec_idx - 1 } else { rec_idx };
+                adj_idx = rec_idx - 1
             }
         }
 
-        let binding = self.recs.remove(rec_idx);
-        let deleted_rec = binding.borrow();
-        let binding = self.cons.remove(con_idx);
-        let deleted_con = binding.borrow();
+        let deleted_rec = self.recs.remove(rec_idx);
+        let deleted_con = self.cons.remove(con_idx);
         if self.config.is_none() {
             return;
         }
 
         let method = self.config();
         if method.deletion_method() == "intelligent" && num_cons > 1 {
-            self.cons[adj_idx]
-                .borrow_mut()
-                .add_mu(deleted_rec.len() as f64 + deleted_con.mu());
+            let adj_con = &mut self.cons[adj_idx];
+            adj_con.add_mu(deleted_rec.len() as f64 + deleted_con.mu());
+            adj_con.add_sigma(deleted_con.sigma());
+            adj_con.refresh();
+        }
+    }
 
-            self.cons[adj_idx]
-                .borrow_mut()
-                .add_sigma(deleted_con.sigma());
+    pub fn insert(&mut self, rec_idx: usize, rec: Recognizer, con: Connector) {
+        let num_recs = self.num_recs();
+        self.threshold = None;
+        if num_recs == 0 {
+            self.recs.push(rec);
             return;
         }
+
+        let intelligent = self
+            .config
+            .as_ref()
+            .is_some_and(|conf| conf.insertion_method() == "intelligent");
+        let rec_len = rec.len() as f64;
+        self.recs.insert(rec_idx, rec);
+        match rec_idx {
+            0 => self.cons.insert(0, con),
+            _ if rec_idx == num_recs => self.cons.push(con),
+            _ if intelligent => {
+                /* split the gap the recognizer lands in between both of its connectors */
+                let old = &mut self.cons[rec_idx - 1];
+                let mu = ((old.mu() - rec_len) / 2.0).max(0.0);
+                let sigma = old.sigma() / 2_f64.sqrt();
+                old.set_mu(mu);
+                old.set_sigma(sigma);
+                old.refresh();
+                let mut con = con;
+                con.set_mu(mu);
+                con.set_sigma(sigma);
+                con.refresh();
+                self.cons.insert(rec_idx, con);
+            }
+            _ => self.cons.insert(rec_idx, con),
+        }
+    }
+
+    pub fn mutate(&mut self, factory: &Factory, rng: &mut impl Rng) {
+        let conf = self.config().clone();
+        self.threshold = None;
+
+        if rng.gen::<f64>() < conf.mutate_probability_delete_recognizer()
+            && self.num_recs() > 1
+            && self.len() >= conf.min_nodes() + 2
+        {
+            let rec_idx = rng.gen_range(0..self.num_recs());
+            self.remove(rec_idx, rng);
+        }
+        if rng.gen::<f64>() < conf.mutate_probability_insert_recognizer()
+            && self.len() + 2 <= conf.max_nodes()
+        {
+            let rec_idx = rng.gen_range(0..self.num_recs() + 1);
+            self.insert(rec_idx, factory.recognizer(rng), factory.connector(rng));
+        }
+        if rng.gen::<f64>() < conf.mutate_probability_substitute_pssm() && self.num_recs() > 0 {
+            let rec_idx = rng.gen_range(0..self.num_recs());
+            self.recs[rec_idx] = factory.recognizer(rng);
+        }
+
+        for rec in self.recs.iter_mut() {
+            if rng.gen::<f64>() < conf.mutate_probability_node_mutation() {
+                rec.mutate(rng);
+            }
+        }
+        for con in self.cons.iter_mut() {
+            if rng.gen::<f64>() < conf.mutate_probability_node_mutation() {
+                con.mutate(rng);
+            }
+        }
+        if self.num_cons() > 1 && rng.gen::<f64>() < factory.con_conf().mutate_probability_swap() {
+            let con_a = rng.gen_range(0..self.num_cons());
+            let con_b = rng.gen_range(0..self.num_cons());
+            self.swap_con(con_a, con_b);
+        }
     }
 
-    pub fn insert(&mut self, rec_idx: usize) {}
+    pub fn recombine(&self, other: &Organism, factory: &Factory, rng: &mut impl Rng) -> Organism {
+        /* one point crossover: the head of self up to a random recognizer joined to the */
+        /* tail of other, bridged by whichever connector sat at either cut point */
+        if self.num_recs() == 0 || other.num_recs() == 0 {
+            return self.clone();
+        }
+        let head = rng.gen_range(1..self.num_recs() + 1);
+        let tail = rng.gen_range(0..other.num_recs());
+
+        let mut recs: Vec<Recognizer> = self.recs[..head].to_vec();
+        recs.extend_from_slice(&other.recs[tail..]);
+        let mut cons: Vec<Connector> = self.cons[..head - 1].to_vec();
+        let bridge = match (head - 1 < self.num_cons(), tail > 0) {
+            (true, _) => self.cons[head - 1].clone(),
+            (false, true) => other.cons[tail - 1].clone(),
+            (false, false) => factory.connector(rng),
+        };
+        cons.push(bridge);
+        cons.extend_from_slice(&other.cons[tail..]);
+
+        let child = organism(recs, cons, None, self.config.clone());
+        if let Some(conf) = &self.config {
+            if child.len() < conf.min_nodes() || child.len() > conf.max_nodes() {
+                return self.clone();
+            }
+        }
+        child
+    }
 
     pub fn print(&self) {
         let len = self.num_recs();
         for i in 0..4 {
             for j in 0..len {
-                let rec = self.recs[j].borrow();
+                let rec = &self.recs[j];
                 let rec_len = rec.len();
                 let matrix = rec.matrix();
                 for k in 0..rec_len {
@@ -145,7 +313,7 @@ impl Organism {
                 }
 
                 if i == 0 && j < len - 1 {
-                    let con = self.cons[j].borrow();
+                    let con = &self.cons[j];
                     let mu = con.mu();
                     let sigma = con.sigma();
                     print!("|--<{:2.2}, {:2.2}>--", mu, sigma);
@@ -153,11 +321,63 @@ impl Organism {
                     print!("|                 ");
                 }
             }
-            println!("");
+            println!();
+        }
+    }
+
+    pub fn to_value(&self) -> Result<Value, OrganismError> {
+        Ok(serde_json::to_value(OrganismData::try_from(self)?)?)
+    }
+
+    pub fn to_json(&self, org_file: &str) -> Result<(), OrganismError> {
+        to_json_list(std::slice::from_ref(self), org_file)
+    }
+
+    pub fn to_pssm(&mut self, background: Option<[f64; 4]>) {
+        /* converts the recognizers read as probabilities, leaving log-odds ones as they are */
+        for rec in self.recs.iter_mut() {
+            if matches!(
+                rec.feat(),
+                RecognizerFeat::Sequence | RecognizerFeat::Kmer(_)
+            ) && !rec.log_odds()
+            {
+                rec.to_pssm(background);
+            }
+        }
+        self.threshold = None;
+    }
+
+    pub fn to_meme(&self, motif_file: &str, log_odds: bool) -> Result<(), OrganismError> {
+        Ok(fs::write(motif_file, self.meme_to_string(log_odds)?)?)
+    }
+
+    pub fn meme_to_string(&self, log_odds: bool) -> Result<String, OrganismError> {
+        let prefix = match self.id {
+            Some(id) => format!("org_{}_", id),
+            None => String::new(),
+        };
+        let mut out = motif::meme_header();
+        for i in 0..self.num_recs() {
+            motif::write_meme_motif(
+                &mut out,
+                &format!("{}rec_{}", prefix, i),
+                &self.recs[i],
+                log_odds,
+            )?;
+            if i < self.num_cons() {
+                let con = &self.cons[i];
+                out.push_str(&format!(
+                    "# connector {}: mu= {:.6} sigma= {:.6}\n\n",
+                    i,
+                    con.mu(),
+                    con.sigma()
+                ));
+            }
         }
+        Ok(out)
     }
 
-    pub fn place(&self, seq: &[char], precomp: Option<&[f64]>) -> Placement {
+    pub fn place(&self, seq: &[u8], _precomp: Option<&[f64]>) -> Placement {
         let num_recs: usize = self.num_recs();
         let min_len: usize = self.len_recs();
         let seq_len: usize = seq.len();
@@ -176,18 +396,18 @@ impl Organism {
         let mut rec_lengths: Vec<usize> = vec![0; num_recs];
 
         for i in 0..num_recs {
-            let curr_rec = self.recs[i].borrow();
+            let curr_rec = &self.recs[i];
             rec_lengths[i] = curr_rec.len();
             r_offset += curr_rec.len();
             curr_rec.calculate_row(&seq[f_offset..r_offset], &mut rs_matrix[i]);
             if i > 0 {
-                let curr_con = self.cons[i - 1].borrow();
+                let curr_con = &self.cons[i - 1];
                 for j in 0..n_align {
-                    for k in 0..(j + 1) {
+                    for (k, c_score) in c_row.iter().enumerate().take(j + 1) {
                         let gap = j - k;
                         let g_score = curr_con.score(gap, seq_len, eff_len, num_recs);
-                        if t_row[j] < c_row[k] + g_score + rs_matrix[i][j] {
-                            t_row[j] = c_row[k] + g_score + rs_matrix[i][j];
+                        if t_row[j] < c_score + g_score + rs_matrix[i][j] {
+                            t_row[j] = c_score + g_score + rs_matrix[i][j];
                             tr_matrix[i - 1][j] = gap;
                             gs_matrix[i - 1][j] = g_score;
                         }
@@ -200,7 +420,7 @@ impl Organism {
             f_offset += curr_rec.len();
         }
         Placement::from_matrix(
-            &seq,
+            seq,
             &rs_matrix,
             &gs_matrix,
             &tr_matrix,
@@ -210,13 +430,94 @@ impl Organism {
         )
     }
 
+    pub fn max_energy(&mut self, seq_len: usize) -> Result<f64, OrganismError> {
+        /* upper bound on place: best score of every recognizer plus the best gap of */
+        /* every connector for a sequence of seq_len */
+        self.check(seq_len)?;
+        let num_recs = self.num_recs();
+        let min_len = self.len_recs();
+        let eff_len = seq_len - min_len + num_recs;
+        let mut energy: f64 = 0.0;
+        for rec in &self.recs {
+            energy += rec.max_score();
+        }
+        for con in &self.cons {
+            energy += (0..seq_len - min_len + 1)
+                .map(|gap| con.score(gap, seq_len, eff_len, num_recs))
+                .fold(f64::NEG_INFINITY, f64::max);
+        }
+        Ok(energy)
+    }
+
+    pub fn compute_threshold(
+        &mut self,
+        background: Option<&Dataset>,
+    ) -> Result<f64, OrganismError> {
+        let seq_len = background.map(|background| background.max_seq_len());
+        let threshold = self.threshold_for(background, seq_len)?;
+        self.threshold = Some(threshold);
+        Ok(threshold)
+    }
+
+    fn threshold_for(
+        &mut self,
+        background: Option<&Dataset>,
+        seq_len: Option<usize>,
+    ) -> Result<f64, OrganismError> {
+        /* fixed: ENERGY_THRESHOLD_PARAM is the threshold */
+        /* percentile: the given percentile of energies on the background set */
+        /* organism: the given percentage of the organism's maximum achievable energy */
+        /* on a sequence of seq_len */
+        let method = self.config().energy_threshold_method().to_string();
+        let param = self.config().energy_threshold_param();
+        match method.as_str() {
+            "fixed" => Ok(param),
+            "percentile" => {
+                let background = background.ok_or(OrganismError::MissingBackgroundError)?;
+                if !(0.0..=100.0).contains(&param) || background.is_empty() {
+                    return Err(OrganismError::ThresholdParamError(param));
+                }
+                let mut energies = fitness::energies(self, background)?;
+                energies.sort_by(|a, b| a.total_cmp(b));
+                let rank = (param / 100.0 * energies.len() as f64).ceil() as usize;
+                Ok(energies[rank.max(1) - 1])
+            }
+            "organism" => {
+                let seq_len = seq_len.ok_or(OrganismError::MissingBackgroundError)?;
+                Ok(self.max_energy(seq_len)? * param / 100.0)
+            }
+            _ => Err(OrganismError::UnknownThresholdMethodError(method)),
+        }
+    }
+
+    pub fn is_bound(&mut self, seq: &[u8]) -> Result<bool, OrganismError> {
+        if self.len_recs() > seq.len() {
+            return Err(OrganismError::ExceedSeqError);
+        }
+        /* without a computed threshold, the organism method uses this sequence's length */
+        let threshold = match self.threshold {
+            Some(threshold) => threshold,
+            None => self.threshold_for(None, Some(seq.len()))?,
+        };
+        self.check(seq.len())?;
+        Ok(self.place(seq, None).energy() >= threshold)
+    }
+
+    pub fn classify(&mut self, dataset: &Dataset) -> Result<Vec<bool>, OrganismError> {
+        let mut bound: Vec<bool> = Vec::with_capacity(dataset.len());
+        for seq in dataset.seqs() {
+            bound.push(self.is_bound(seq.seq())?);
+        }
+        Ok(bound)
+    }
+
     pub fn check(&mut self, seq_len: usize) -> Result<(), OrganismError> {
         if self.len_recs() > seq_len {
             return Err(OrganismError::ExceedSeqError);
         }
 
-        for i in 0..self.num_cons() {
-            let mut con = self.con_at_mut(i).borrow_mut();
+        /* extending the connector tables is not a mutation, so leave the threshold cached */
+        for con in self.cons.iter_mut() {
             if seq_len > con.len() {
                 con.compute_until(seq_len);
             }
@@ -226,39 +527,166 @@ impl Organism {
     }
 }
 
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(untagged)]
+enum NodeData {
+    Recognizer(RecognizerData),
+    Connector(ConnectorData),
+}
+
+/* the serialized form of an organism, a bare node array is read as version 1 */
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(untagged)]
+enum OrganismData {
+    Versioned {
+        version: usize,
+        nodes: Vec<NodeData>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        id: Option<usize>,
+    },
+    V1(Vec<NodeData>),
+}
+
+impl TryFrom<&Organism> for OrganismData {
+    type Error = OrganismError;
+
+    fn try_from(org: &Organism) -> Result<OrganismData, OrganismError> {
+        /* nodes alternate recognizer, connector, recognizer, as in organism.json */
+        let mut nodes: Vec<NodeData> = Vec::with_capacity(org.len());
+        for (i, rec) in org.recs.iter().enumerate() {
+            nodes.push(NodeData::Recognizer(RecognizerData::try_from(rec)?));
+            if let Some(con) = org.cons.get(i) {
+                nodes.push(NodeData::Connector(ConnectorData::from(con)));
+            }
+        }
+        Ok(OrganismData::Versioned {
+            version: ORGANISM_VERSION,
+            nodes,
+            id: org.id,
+        })
+    }
+}
+
+impl TryFrom<OrganismData> for Organism {
+    type Error = OrganismError;
+
+    fn try_from(data: OrganismData) -> Result<Organism, OrganismError> {
+        /* comes without configs, attach them with set_config */
+        let (nodes, id) = match data {
+            OrganismData::Versioned { version, nodes, id } => {
+                check_version(version)?;
+                (nodes, id)
+            }
+            OrganismData::V1(nodes) => (nodes, None),
+        };
+        check_nodes(nodes.len())?;
+        let mut recs: Vec<Recognizer> = Vec::new();
+        let mut cons: Vec<Connector> = Vec::new();
+
+        for (i, node) in nodes.into_iter().enumerate() {
+            match (node, i % 2) {
+                (NodeData::Recognizer(rec), 0) => recs.push(
+                    Recognizer::try_from(rec).map_err(|e| OrganismError::from(e).at_node(i))?,
+                ),
+                (NodeData::Connector(con), 1) => cons
+                    .push(Connector::try_from(con).map_err(|e| OrganismError::from(e).at_node(i))?),
+                _ => return Err(node_order_error(i).into()),
+            }
+        }
+        check_last_node(&recs, &cons)?;
+
+        Ok(organism(recs, cons, id, None))
+    }
+}
+
+impl Serialize for Organism {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        OrganismData::try_from(self)
+            .map_err(ser::Error::custom)?
+            .serialize(serializer)
+    }
+}
+
+fn check_version(version: usize) -> Result<(), ParseError> {
+    if version == 0 || version > ORGANISM_VERSION {
+        return Err(ParseError::new(
+            ".version",
+            &format!("version between 1 and {}", ORGANISM_VERSION),
+        ));
+    }
+    Ok(())
+}
+
+fn check_nodes(len: usize) -> Result<(), ParseError> {
+    /* nodes alternate recognizer, connector, ..., recognizer */
+    if len == 0 {
+        return Err(ParseError::new("", "non-empty array of nodes"));
+    }
+    Ok(())
+}
+
+fn node_order_error(node: usize) -> ParseError {
+    let expected = match node % 2 {
+        0 => "one of pssm, kpssm, shape",
+        _ => "connector",
+    };
+    ParseError::new(".objectType", expected).at_node(node)
+}
+
+fn check_last_node(recs: &[Recognizer], cons: &[Connector]) -> Result<(), ParseError> {
+    if recs.len() == cons.len() {
+        return Err(ParseError::new("", "recognizer").at_node(recs.len() + cons.len()));
+    }
+    Ok(())
+}
+
 pub fn from_value(
     org: &Value,
     org_conf: Option<&OrganismConfig>,
     rec_conf: Option<&RecognizerConfig>,
     con_conf: Option<&ConnectorConfig>,
 ) -> Result<Organism, OrganismError> {
-    let nodes = org.as_array().unwrap();
-    let num_nodes = nodes.len();
-    let mut recs: Vec<cell::RefCell<Recognizer>> = Vec::new();
-    let mut cons: Vec<cell::RefCell<Connector>> = Vec::new();
-
-    for i in 0..num_nodes {
-        match nodes[i].as_object().unwrap()["objectType"]
-            .as_str()
-            .unwrap()
-        {
-            "pssm" => recs.push(cell::RefCell::new(recognizer::from_value(
-                &nodes[i], rec_conf,
-            )?)),
-            "connector" => cons.push(cell::RefCell::new(connector::from_value(
-                &nodes[i], con_conf,
-            )?)),
-            "shape" => break,
-            _ => break,
+    let nodes = match org {
+        Value::Array(nodes) => nodes,
+        Value::Object(obj) => {
+            check_version(aux::as_usize(aux::field(obj, "version"), ".version")?)?;
+            aux::as_array(aux::field(obj, "nodes"), ".nodes")?
+        }
+        _ => return Err(ParseError::new("", "array of nodes or versioned object").into()),
+    };
+    let id = match org.get("id") {
+        None | Some(Value::Null) => None,
+        Some(id) => Some(aux::as_usize(id, ".id")?),
+    };
+
+    check_nodes(nodes.len())?;
+    let mut recs: Vec<Recognizer> = Vec::new();
+    let mut cons: Vec<Connector> = Vec::new();
+
+    for (i, node) in nodes.iter().enumerate() {
+        let obj = aux::as_object(node, "").map_err(|e| e.at_node(i))?;
+        let node_type =
+            aux::as_str(aux::field(obj, "objectType"), ".objectType").map_err(|e| e.at_node(i))?;
+        match (node_type, i % 2) {
+            ("pssm" | "kpssm" | "shape", 0) => recs.push(
+                recognizer::from_value(node, rec_conf)
+                    .map_err(|e| OrganismError::from(e).at_node(i))?,
+            ),
+            ("connector", 1) => cons.push(
+                connector::from_value(node, con_conf)
+                    .map_err(|e| OrganismError::from(e).at_node(i))?,
+            ),
+            _ => return Err(node_order_error(i).into()),
         }
     }
+    check_last_node(&recs, &cons)?;
 
-    Ok(organism(recs, cons, None, org_conf.cloned()))
+    Ok(organism(recs, cons, id, org_conf.cloned()))
 }
 
 pub fn organism(
-    recs: Vec<cell::RefCell<recognizer::Recognizer>>,
-    cons: Vec<cell::RefCell<connector::Connector>>,
+    recs: Vec<Recognizer>,
+    cons: Vec<Connector>,
     id: Option<usize>,
     config: Option<OrganismConfig>,
 ) -> Organism {
@@ -267,6 +695,7 @@ pub fn organism(
         cons,
         id,
         config,
+        threshold: None,
     }
 }
 
@@ -278,73 +707,356 @@ pub fn from_json(
     let org_file = fs::File::open(org_file)?;
     let org_reader = io::BufReader::new(org_file);
     let org_value: Value = serde_json::from_reader(org_reader)?;
+    let org = aux::as_array(&org_value, "")?
+        .get(org_num)
+        .ok_or_else(|| ParseError::new("", "organism").at_org(org_num))?;
+
+    let conf = match conf_file {
+        Some(conf_file) => Some(config::load_files(&[conf_file])?),
+        None => None,
+    };
+    from_value(
+        org,
+        conf.as_ref().map(|conf| conf.organism()),
+        conf.as_ref().map(|conf| conf.recognizer()),
+        conf.as_ref().map(|conf| conf.connector()),
+    )
+    .map_err(|e| e.at_org(org_num))
+}
 
-    match conf_file.is_some() {
-        true => {
-            let conf_file = fs::File::open(conf_file.ok_or_else(|| {
-                OrganismError::IOError(io::Error::new(io::ErrorKind::NotFound, "oh no"))
-            })?)?;
-            let conf_reader = io::BufReader::new(conf_file);
-            let conf_value: Value = serde_json::from_reader(conf_reader)?;
-            let org_conf: OrganismConfig = serde_json::from_value(conf_value["organism"].clone())?;
-            let rec_conf: RecognizerConfig =
-                serde_json::from_value(conf_value["recognizer"].clone())?;
-            let con_conf: ConnectorConfig =
-                serde_json::from_value(conf_value["connector"].clone())?;
-            Ok(from_value(
-                &org_value[org_num],
-                Some(&org_conf),
-                Some(&rec_conf),
-                Some(&con_conf),
-            )?)
-        }
-        false => Ok(from_value(&org_value[org_num], None, None, None)?),
+pub fn to_json_list(orgs: &[Organism], org_file: &str) -> Result<(), OrganismError> {
+    /* written as a list of organisms, so from_json and from_json_list read it back */
+    let mut org_values: Vec<Value> = Vec::with_capacity(orgs.len());
+    for org in orgs {
+        org_values.push(org.to_value()?);
     }
+    let org_writer = io::BufWriter::new(fs::File::create(org_file)?);
+    serde_json::to_writer_pretty(org_writer, &Value::Array(org_values))?;
+    Ok(())
 }
 
 pub fn from_json_list(
     org_file: &str,
     conf_file: Option<&str>,
 ) -> Result<Vec<Organism>, OrganismError> {
-    let org_file = fs::File::open(org_file)?;
-    let org_reader = io::BufReader::new(org_file);
-    let org_value: Value = serde_json::from_reader(org_reader)?;
-    let num_orgs: usize = org_value
-        .as_array()
-        .ok_or_else(|| {
-            OrganismError::ParseJSONError(serde::de::Error::invalid_type(
-                serde::de::Unexpected::Option,
-                &"hi",
-            ))
-        })?
-        .len();
-    let mut orgs: Vec<Organism> = Vec::with_capacity(num_orgs);
-    match conf_file.is_some() {
-        true => {
-            let conf_file = fs::File::open(conf_file.ok_or_else(|| {
-                OrganismError::IOError(io::Error::new(io::ErrorKind::NotFound, "oh no"))
-            })?)?;
-            let conf_reader = io::BufReader::new(conf_file);
-            let conf_value: Value = serde_json::from_reader(conf_reader)?;
-            let org_conf: OrganismConfig = serde_json::from_value(conf_value["organism"].clone())?;
-            let rec_conf: RecognizerConfig =
-                serde_json::from_value(conf_value["recognizer"].clone())?;
-            let con_conf: ConnectorConfig =
-                serde_json::from_value(conf_value["connector"].clone())?;
-            for i in 0..num_orgs {
-                orgs.push(from_value(
-                    &org_value[i],
-                    Some(&org_conf),
-                    Some(&rec_conf),
-                    Some(&con_conf),
-                )?);
+    let conf = match conf_file {
+        Some(conf_file) => Some(config::load_files(&[conf_file])?),
+        None => None,
+    };
+    organisms_from_reader(fs::File::open(org_file)?, conf.as_ref()).collect()
+}
+
+pub fn organisms_from_reader<R: Read>(
+    reader: R,
+    config: Option<&Config>,
+) -> impl Iterator<Item = Result<Organism, OrganismError>> {
+    OrganismReader {
+        reader: BufReader::new(reader),
+        config: config.cloned(),
+        org_idx: 0,
+        started: false,
+        done: false,
+    }
+}
+
+impl<R: BufRead> OrganismReader<R> {
+    fn next_org(&mut self) -> Result<Option<Organism>, OrganismError> {
+        let org = match self.next_element()? {
+            Some(org) => org,
+            None => return Ok(None),
+        };
+        let org_idx = self.org_idx;
+        self.org_idx += 1;
+        let org: Value = serde_json::from_slice(&org)?;
+        from_value(
+            &org,
+            self.config.as_ref().map(|conf| conf.organism()),
+            self.config.as_ref().map(|conf| conf.recognizer()),
+            self.config.as_ref().map(|conf| conf.connector()),
+        )
+        .map(Some)
+        .map_err(|e| e.at_org(org_idx))
+    }
+
+    fn next_element(&mut self) -> Result<Option<Vec<u8>>, OrganismError> {
+        /* steps over the outer [ and the commas, then copies out one organism by */
+        /* bracket depth, so only a single organism is ever held in memory */
+        let sep = self.skip_whitespace()?;
+        let first = match (self.started, sep) {
+            (false, Some(b'[')) => {
+                self.started = true;
+                self.reader.consume(1);
+                match self.skip_whitespace()? {
+                    Some(b']') => return self.end(),
+                    first => first,
+                }
+            }
+            (false, _) => return Err(ParseError::new("", "array of organisms").into()),
+            (true, Some(b',')) => {
+                self.reader.consume(1);
+                self.skip_whitespace()?
+            }
+            (true, Some(b']')) => return self.end(),
+            (true, _) => return Err(ParseError::new("", "',' or ']'").into()),
+        };
+        if !matches!(first, Some(b'[') | Some(b'{')) {
+            return Err(ParseError::new("", "array of nodes or versioned object")
+                .at_org(self.org_idx)
+                .into());
+        }
+
+        let mut org: Vec<u8> = Vec::new();
+        let mut depth: usize = 0;
+        let mut in_str = false;
+        let mut escaped = false;
+        loop {
+            let buf = self.reader.fill_buf()?;
+            if buf.is_empty() {
+                return Err(ParseError::new("", "complete organism")
+                    .at_org(self.org_idx)
+                    .into());
+            }
+            let mut used: usize = 0;
+            let mut closed = false;
+            for &byte in buf {
+                used += 1;
+                if in_str {
+                    match byte {
+                        _ if escaped => escaped = false,
+                        b'\\' => escaped = true,
+                        b'"' => in_str = false,
+                        _ => {}
+                    }
+                    continue;
+                }
+                match byte {
+                    b'"' => in_str = true,
+                    b'[' | b'{' => depth += 1,
+                    b']' | b'}' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            closed = true;
+                            break;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            org.extend_from_slice(&buf[..used]);
+            self.reader.consume(used);
+            if closed {
+                return Ok(Some(org));
             }
         }
-        false => {
-            for i in 0..num_orgs {
-                orgs.push(from_value(&org_value[i], None, None, None)?);
+    }
+
+    fn end(&mut self) -> Result<Option<Vec<u8>>, OrganismError> {
+        self.reader.consume(1);
+        self.done = true;
+        match self.skip_whitespace()? {
+            None => Ok(None),
+            Some(_) => Err(ParseError::new("", "end of file after the organisms").into()),
+        }
+    }
+
+    fn skip_whitespace(&mut self) -> Result<Option<u8>, OrganismError> {
+        /* returns the next byte without consuming it */
+        loop {
+            let buf = self.reader.fill_buf()?;
+            match buf.iter().position(|byte| !byte.is_ascii_whitespace()) {
+                Some(pos) => {
+                    let byte = buf[pos];
+                    self.reader.consume(pos);
+                    return Ok(Some(byte));
+                }
+                None if buf.is_empty() => return Ok(None),
+                None => {
+                    let len = buf.len();
+                    self.reader.consume(len);
+                }
             }
         }
     }
-    Ok(orgs)
+}
+
+impl<R: BufRead> Iterator for OrganismReader<R> {
+    type Item = Result<Organism, OrganismError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let org = self.next_org();
+        aux::stop_on_error(&mut self.done, org)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Map};
+
+    fn parse_error(org: &Value) -> ParseError {
+        match from_value(org, None, None, None) {
+            Err(OrganismError::ParseError(e)) => e,
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+    }
+
+    fn nodes() -> Value {
+        json!([
+            {"objectType": "pssm", "pwm": [{"a": 1.0, "c": 0.0, "g": 0.0, "t": 0.0}]},
+            {"objectType": "connector", "mu": 2.0, "sigma": 1.0},
+            {"objectType": "pssm", "pwm": [
+                {"a": 0.0, "c": 1.0, "g": 0.0, "t": 0.0},
+                {"a": 0.0, "c": 0.0, "g": 1.0, "t": 0.0}
+            ]}
+        ])
+    }
+
+    #[test]
+    fn parse_errors_point_at_the_bad_field() {
+        let mut org = nodes();
+        org[2]["pwm"][1]["g"] = json!("high");
+        let e = parse_error(&org);
+        assert_eq!((e.org(), e.node()), (None, Some(2)));
+        assert_eq!(e.path(), "[2].pwm[1].g");
+        assert_eq!(e.expected(), "number");
+
+        let mut org = nodes();
+        org[1]["sigma"] = json!(-1.0);
+        assert_eq!(parse_error(&org).path(), "[1].sigma");
+
+        let mut org = nodes();
+        org[1]["objectType"] = json!("pssm");
+        assert_eq!(parse_error(&org).path(), "[1].objectType");
+
+        let org = json!({"version": 3, "nodes": nodes()});
+        assert_eq!(parse_error(&org).path(), ".version");
+
+        let mut org = nodes();
+        org.as_array_mut().unwrap().pop();
+        let e = parse_error(&org);
+        assert_eq!((e.path().as_str(), e.expected()), ("[2]", "recognizer"));
+    }
+
+    #[test]
+    fn parse_errors_name_the_organism_in_a_list() {
+        let mut bad = nodes();
+        bad[0]["pwm"][0]["a"] = json!(null);
+        let list = serde_json::to_string(&json!([nodes(), bad])).unwrap();
+        let orgs: Vec<Result<Organism, OrganismError>> =
+            organisms_from_reader(list.as_bytes(), None).collect();
+        assert_eq!(orgs.len(), 2);
+        assert!(orgs[0].is_ok());
+        let e = match &orgs[1] {
+            Err(OrganismError::ParseError(e)) => e,
+            other => panic!("expected a parse error, got {:?}", other),
+        };
+        assert_eq!(e.path(), "[1][0].pwm[0].a");
+        assert_eq!(
+            e.to_string(),
+            "organism 1, node 0, [1][0].pwm[0].a: expected number"
+        );
+    }
+
+    fn mixed_nodes() -> Value {
+        /* one node of each kind: pssm, connector, kpssm, connector, shape */
+        let col: Map<String, Value> = ["aa", "ac", "ag", "at", "ca", "cc", "cg", "ct"]
+            .iter()
+            .chain(["ga", "gc", "gg", "gt", "ta", "tc", "tg", "tt"].iter())
+            .enumerate()
+            .map(|(i, kmer)| (kmer.to_string(), json!(i as f64 * 0.25 - 2.0)))
+            .collect();
+        let mut nodes = nodes();
+        let nodes = nodes.as_array_mut().unwrap();
+        nodes[2] = json!({"objectType": "kpssm", "order": 1, "pwm": [col.clone(), col]});
+        nodes.push(json!({"objectType": "connector", "mu": 0.5, "sigma": 3.0}));
+        nodes.push(
+            json!({"objectType": "shape", "feature": "MGW", "mu": 5.1, "sigma": 0.4, "length": 6}),
+        );
+        Value::Array(nodes.clone())
+    }
+
+    #[test]
+    fn json_round_trips_v1_and_v2() {
+        let v1 = mixed_nodes();
+        let org: Organism = serde_json::from_value(v1.clone()).unwrap();
+        assert_eq!((org.num_recs(), org.num_cons(), org.id), (3, 2, None));
+        assert_eq!(
+            org.to_value().unwrap(),
+            json!({"version": ORGANISM_VERSION, "nodes": v1})
+        );
+        let parsed = from_value(&v1, None, None, None).unwrap();
+        assert_eq!(parsed.to_value().unwrap(), org.to_value().unwrap());
+
+        let mut org = org;
+        org.id = Some(7);
+        let v2 = serde_json::to_string(&org).unwrap();
+        let back: Organism = serde_json::from_str(&v2).unwrap();
+        assert_eq!(back.id, Some(7));
+        assert_eq!(back.to_value().unwrap(), org.to_value().unwrap());
+        assert_eq!(serde_json::to_string(&back).unwrap(), v2);
+    }
+
+    #[test]
+    fn json_round_trips_log_odds_organisms() {
+        /* the zero probabilities of nodes() become -inf log-odds */
+        let mut org = from_value(&nodes(), None, None, None).unwrap();
+        org.to_pssm(None);
+        assert_eq!(org.rec_at(0).matrix()[1], f64::NEG_INFINITY);
+        let value = org.to_value().unwrap();
+        assert_eq!(value["nodes"][0]["pwm"][0]["c"], "-inf");
+
+        let mut read = from_value(&value, None, None, None).unwrap();
+        read.to_pssm(None);
+        let json: Organism = serde_json::from_str(&value.to_string()).unwrap();
+        for read in [read, json] {
+            for i in 0..org.num_recs() {
+                assert!(read.rec_at(i).log_odds());
+                assert_eq!(read.rec_at(i).matrix(), org.rec_at(i).matrix());
+            }
+            assert_eq!(read.to_value().unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn json_rejects_bad_versions_and_node_order() {
+        let org = json!({"version": 3, "nodes": nodes()});
+        assert!(serde_json::from_value::<Organism>(org).is_err());
+        let org = json!({"version": 1, "nodes": nodes()});
+        assert!(serde_json::from_value::<Organism>(org).is_ok());
+
+        let mut org = nodes();
+        org.as_array_mut().unwrap().swap(0, 1);
+        assert!(serde_json::from_value::<Organism>(org).is_err());
+        let mut org = nodes();
+        org[1]["sigma"] = json!(0.0);
+        assert!(serde_json::from_value::<Organism>(org).is_err());
+    }
+
+    #[test]
+    fn classify_keeps_the_cached_threshold() {
+        let seqs = ">s1\nacgtacgtggcatgca\n>s2\ntttacgtacgaacgtcatgcatgc\n>s3\nggggacgtacgtccccacgtaaaaacgtttgc\n";
+        let background = dataset::fasta_from_reader(seqs.as_bytes(), "bg.fa").unwrap();
+        for (method, param) in [("percentile", 50.0), ("organism", 80.0)] {
+            let mut conf = serde_json::to_value(Config::default()).unwrap();
+            config::merge(
+                &mut conf,
+                json!({"organism": {
+                    "ENERGY_THRESHOLD_METHOD": method,
+                    "ENERGY_THRESHOLD_PARAM": param,
+                }}),
+            );
+            let conf: Config = serde_json::from_value(conf).unwrap();
+            let mut org = from_value(&nodes(), None, None, None).unwrap();
+            org.set_config(&conf);
+
+            let threshold = org.compute_threshold(Some(&background)).unwrap();
+            let bound = org.classify(&background).unwrap();
+            assert_eq!(bound.len(), 3);
+            assert_eq!(org.threshold(), Some(threshold));
+            assert!(bound.iter().any(|bound| *bound));
+        }
+    }
 }
@@ -1,27 +1,22 @@
-use organism;
 use std::time::Instant;
 
 fn main() {
-    let mut seq: Vec<char> = Vec::new();
+    let mut seq: Vec<u8> = Vec::new();
     for _i in 0..50 {
-        for b in ['a', 'c', 'g', 't'] {
-            seq.push(b);
-        }
+        seq.extend_from_slice(b"acgt");
     }
     let mut org: organism::Organism =
         organism::from_json("organism.json", 0, Some("config.json")).unwrap();
     println!("Flipping row 0");
     org.print();
-    println!("");
-    //org.rec_at_mut(0).borrow_mut().flip_row(0);
-    println!("");
+    println!();
+    //org.rec_at_mut(0).flip_row(0);
+    println!();
     org.print();
     //org.remove(2);
-    println!("");
+    println!();
 
-    for rec in 0..org.num_recs() {
-        org.rec_at_mut(rec).borrow_mut().to_pssm();
-    }
+    org.to_pssm(None);
     org.print();
 
     let now = Instant::now();
@@ -0,0 +1,388 @@
+use crate::error::RecognizerError;
+use crate::recognizer::{self, Recognizer, RecognizerFeat};
+use std::fmt::Write;
+use std::fs;
+
+const BASES: [char; 4] = ['A', 'C', 'G', 'T'];
+
+pub fn from_meme(motif_file: &str) -> Result<Vec<Recognizer>, RecognizerError> {
+    meme_from_str(&fs::read_to_string(motif_file)?)
+}
+
+pub fn from_jaspar(motif_file: &str) -> Result<Vec<Recognizer>, RecognizerError> {
+    jaspar_from_str(&fs::read_to_string(motif_file)?)
+}
+
+pub fn from_transfac(motif_file: &str) -> Result<Vec<Recognizer>, RecognizerError> {
+    transfac_from_str(&fs::read_to_string(motif_file)?)
+}
+
+pub fn meme_from_str(text: &str) -> Result<Vec<Recognizer>, RecognizerError> {
+    let mut recs: Vec<Recognizer> = Vec::new();
+    let mut lines = text.lines().map(str::trim).peekable();
+
+    while let Some(line) = lines.next() {
+        if !line.starts_with("letter-probability matrix") {
+            continue;
+        }
+
+        let width: Option<usize> = line
+            .split_whitespace()
+            .skip_while(|tok| *tok != "w=")
+            .nth(1)
+            .and_then(|tok| tok.parse().ok());
+
+        let mut cols: Vec<[f64; 4]> = Vec::new();
+        while let Some(row) = lines.peek() {
+            if width == Some(cols.len()) {
+                break;
+            }
+            if row.is_empty() {
+                lines.next();
+                if cols.is_empty() {
+                    continue;
+                }
+                break;
+            }
+            match parse_row(row) {
+                Some(col) => cols.push(col),
+                None => break,
+            }
+            lines.next();
+        }
+
+        if width.is_some_and(|w| cols.len() != w) {
+            return Err(RecognizerError::ParseMotifError(format!(
+                "MEME motif {} declares w= {} but has {} rows",
+                recs.len() + 1,
+                width.unwrap_or_default(),
+                cols.len()
+            )));
+        }
+        recs.push(from_cols(&cols)?);
+    }
+
+    if recs.is_empty() {
+        return Err(RecognizerError::ParseMotifError(
+            "no letter-probability matrix found in MEME file".to_string(),
+        ));
+    }
+    Ok(recs)
+}
+
+pub fn jaspar_from_str(text: &str) -> Result<Vec<Recognizer>, RecognizerError> {
+    /* handles both the bracketed jaspar flavour and the bare pfm flavour, */
+    /* with or without > headers separating motifs */
+    let mut recs: Vec<Recognizer> = Vec::new();
+    let mut rows: Vec<Vec<f64>> = Vec::new();
+
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('>') {
+            continue;
+        }
+
+        let line = line.trim_start_matches(|c: char| BASES.contains(&c.to_ascii_uppercase()));
+        let row: Vec<f64> = line
+            .split(|c: char| c.is_whitespace() || c == '[' || c == ']')
+            .filter(|tok| !tok.is_empty())
+            .map(|tok| tok.parse::<f64>())
+            .collect::<Result<_, _>>()
+            .map_err(|_| {
+                RecognizerError::ParseMotifError(format!("invalid JASPAR row: {}", line))
+            })?;
+        rows.push(row);
+
+        if rows.len() == 4 {
+            let len = rows[0].len();
+            if rows.iter().any(|row| row.len() != len) {
+                return Err(RecognizerError::ParseMotifError(format!(
+                    "JASPAR motif {} has rows of unequal length",
+                    recs.len() + 1
+                )));
+            }
+            let cols: Vec<[f64; 4]> = (0..len)
+                .map(|i| [rows[0][i], rows[1][i], rows[2][i], rows[3][i]])
+                .collect();
+            recs.push(from_cols(&cols)?);
+            rows.clear();
+        }
+    }
+
+    if !rows.is_empty() {
+        return Err(RecognizerError::ParseMotifError(format!(
+            "JASPAR motif {} has {} rows instead of 4",
+            recs.len() + 1,
+            rows.len()
+        )));
+    }
+    if recs.is_empty() {
+        return Err(RecognizerError::ParseMotifError(
+            "no matrix found in JASPAR file".to_string(),
+        ));
+    }
+    Ok(recs)
+}
+
+pub fn transfac_from_str(text: &str) -> Result<Vec<Recognizer>, RecognizerError> {
+    let mut recs: Vec<Recognizer> = Vec::new();
+    let mut order: Option<[usize; 4]> = None;
+    let mut cols: Vec<[f64; 4]> = Vec::new();
+
+    for line in text.lines().map(str::trim) {
+        let mut toks = line.split_whitespace();
+        let tag = match toks.next() {
+            Some(tag) => tag,
+            None => continue,
+        };
+
+        if tag == "P0" || tag == "PO" {
+            let header: Vec<char> = toks.filter_map(|tok| tok.chars().next()).collect();
+            let mut idx = [0; 4];
+            for j in 0..BASES.len() {
+                idx[j] = header
+                    .iter()
+                    .position(|c| c.to_ascii_uppercase() == BASES[j])
+                    .ok_or_else(|| {
+                        RecognizerError::ParseMotifError(format!(
+                            "TRANSFAC header is missing base {}",
+                            BASES[j]
+                        ))
+                    })?;
+            }
+            order = Some(idx);
+            continue;
+        }
+
+        match order {
+            Some(idx) if tag.chars().all(|c| c.is_ascii_digit()) => {
+                let vals: Vec<f64> = toks.filter_map(|tok| tok.parse().ok()).collect();
+                if idx.iter().any(|i| *i >= vals.len()) {
+                    return Err(RecognizerError::ParseMotifError(format!(
+                        "invalid TRANSFAC row: {}",
+                        line
+                    )));
+                }
+                cols.push([vals[idx[0]], vals[idx[1]], vals[idx[2]], vals[idx[3]]]);
+            }
+            Some(_) if tag == "XX" || tag == "//" => {
+                if !cols.is_empty() {
+                    recs.push(from_cols(&cols)?);
+                    cols.clear();
+                }
+                order = None;
+            }
+            _ => continue,
+        }
+    }
+
+    if !cols.is_empty() {
+        recs.push(from_cols(&cols)?);
+    }
+    if recs.is_empty() {
+        return Err(RecognizerError::ParseMotifError(
+            "no P0 matrix found in TRANSFAC file".to_string(),
+        ));
+    }
+    Ok(recs)
+}
+
+pub fn to_meme(
+    motif_file: &str,
+    recs: &[Recognizer],
+    log_odds: bool,
+) -> Result<(), RecognizerError> {
+    Ok(fs::write(motif_file, meme_to_string(recs, log_odds)?)?)
+}
+
+pub fn to_jaspar(
+    motif_file: &str,
+    recs: &[Recognizer],
+    log_odds: bool,
+) -> Result<(), RecognizerError> {
+    Ok(fs::write(motif_file, jaspar_to_string(recs, log_odds)?)?)
+}
+
+pub fn meme_to_string(recs: &[Recognizer], log_odds: bool) -> Result<String, RecognizerError> {
+    let mut out = meme_header();
+    for (i, rec) in recs.iter().enumerate() {
+        write_meme_motif(&mut out, &format!("rec_{}", i), rec, log_odds)?;
+    }
+    Ok(out)
+}
+
+pub fn jaspar_to_string(recs: &[Recognizer], log_odds: bool) -> Result<String, RecognizerError> {
+    let mut out = String::new();
+    for (i, rec) in recs.iter().enumerate() {
+        let matrix = export_matrix(rec, log_odds)?;
+        writeln!(out, ">rec_{}\trec_{}", i, i).unwrap();
+        for (j, base) in BASES.iter().enumerate() {
+            write!(out, "{} [", base).unwrap();
+            for k in 0..rec.len() {
+                write!(out, " {:.6}", matrix[k * 4 + j]).unwrap();
+            }
+            writeln!(out, " ]").unwrap();
+        }
+    }
+    Ok(out)
+}
+
+pub(crate) fn meme_header() -> String {
+    let mut out = String::new();
+    writeln!(out, "MEME version 4\n").unwrap();
+    writeln!(out, "ALPHABET= ACGT\n").unwrap();
+    writeln!(out, "strands: + -\n").unwrap();
+    writeln!(out, "Background letter frequencies").unwrap();
+    writeln!(out, "A 0.25 C 0.25 G 0.25 T 0.25\n").unwrap();
+    out
+}
+
+pub(crate) fn write_meme_motif(
+    out: &mut String,
+    name: &str,
+    rec: &Recognizer,
+    log_odds: bool,
+) -> Result<(), RecognizerError> {
+    let matrix = export_matrix(rec, log_odds)?;
+    writeln!(out, "MOTIF {}", name).unwrap();
+    /* nsites and E are optional and a recognizer does not know its site count */
+    writeln!(
+        out,
+        "letter-probability matrix: alength= 4 w= {}",
+        rec.len()
+    )
+    .unwrap();
+    for i in 0..rec.len() {
+        for j in 0..BASES.len() {
+            write!(out, " {:.6}", matrix[i * 4 + j]).unwrap();
+        }
+        writeln!(out).unwrap();
+    }
+    writeln!(out).unwrap();
+    Ok(())
+}
+
+fn export_matrix(rec: &Recognizer, log_odds: bool) -> Result<Vec<f64>, RecognizerError> {
+    match rec.feat() {
+        RecognizerFeat::Sequence => {}
+        feat => {
+            return Err(RecognizerError::ExportMotifError(format!(
+                "cannot write {:?} recognizer as a letter-probability matrix",
+                feat
+            )))
+        }
+    }
+
+    if !log_odds {
+        return Ok(rec.matrix().clone());
+    }
+    let mut rec = rec.clone();
+    rec.to_pwm(None);
+    Ok(rec.matrix().clone())
+}
+
+fn parse_row(row: &str) -> Option<[f64; 4]> {
+    let vals: Vec<f64> = row
+        .split_whitespace()
+        .map(|tok| tok.parse().ok())
+        .collect::<Option<_>>()?;
+    match vals.len() {
+        4 => Some([vals[0], vals[1], vals[2], vals[3]]),
+        _ => None,
+    }
+}
+
+fn from_cols(cols: &[[f64; 4]]) -> Result<Recognizer, RecognizerError> {
+    let len = cols.len();
+    let mut matrix: Vec<f64> = vec![0.00; len * 4];
+    for (i, col) in cols.iter().enumerate() {
+        let total: f64 = col.iter().sum();
+        if total <= 0.0 {
+            return Err(RecognizerError::ParseMotifError(format!(
+                "column {} has no counts",
+                i
+            )));
+        }
+        for (j, count) in col.iter().enumerate() {
+            matrix[i * 4 + j] = count / total;
+        }
+    }
+    Ok(recognizer::pssm(
+        RecognizerFeat::Sequence,
+        len,
+        Some(matrix),
+        None,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_cols(rec: &Recognizer, cols: &[[f64; 4]]) {
+        assert_eq!(rec.len(), cols.len());
+        for (i, col) in cols.iter().enumerate() {
+            for (j, val) in col.iter().enumerate() {
+                assert!((rec.matrix()[i * 4 + j] - val).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn parses_meme() {
+        let text = "MEME version 4\n\nALPHABET= ACGT\n\n\
+            MOTIF one\nletter-probability matrix: alength= 4 w= 2 nsites= 4 E= 0\n\
+            1.0 0.0 0.0 0.0\n0.25 0.25 0.25 0.25\n\n\
+            MOTIF two\nletter-probability matrix: alength= 4 w= 1\n0.1 0.2 0.3 0.4\n";
+        let recs = meme_from_str(text).unwrap();
+        assert_eq!(recs.len(), 2);
+        assert_cols(&recs[0], &[[1.0, 0.0, 0.0, 0.0], [0.25; 4]]);
+        assert_cols(&recs[1], &[[0.1, 0.2, 0.3, 0.4]]);
+        assert!(meme_from_str("MOTIF x\nletter-probability matrix: w= 3\n1 0 0 0\n").is_err());
+        assert!(meme_from_str("no motifs here").is_err());
+    }
+
+    #[test]
+    fn parses_jaspar() {
+        let bracketed = ">MA0001.1 one\nA [ 3 0 ]\nC [ 1 0 ]\nG [ 0 4 ]\nT [ 0 0 ]\n";
+        let bare = "3 0\n1 0\n0 4\n0 0\n";
+        for text in [bracketed, bare] {
+            let recs = jaspar_from_str(text).unwrap();
+            assert_eq!(recs.len(), 1);
+            assert_cols(&recs[0], &[[0.75, 0.25, 0.0, 0.0], [0.0, 0.0, 1.0, 0.0]]);
+        }
+        assert!(jaspar_from_str("A [ 1 2 ]\nC [ 1 ]\nG [ 1 2 ]\nT [ 1 2 ]\n").is_err());
+        assert!(jaspar_from_str("A [ 1 ]\nC [ 1 ]\n").is_err());
+    }
+
+    #[test]
+    fn meme_and_jaspar_exports_read_back() {
+        let recs = vec![
+            Recognizer::from_sites(&["acgt", "acga", "tcgt"], 0.5, None).unwrap(),
+            Recognizer::from_sites(&["ggc"], 0.1, None).unwrap(),
+        ];
+        let meme = meme_to_string(&recs, false).unwrap();
+        assert!(!meme.contains("nsites"));
+        let jaspar = jaspar_to_string(&recs, false).unwrap();
+        for back in [
+            meme_from_str(&meme).unwrap(),
+            jaspar_from_str(&jaspar).unwrap(),
+        ] {
+            assert_eq!(back.len(), recs.len());
+            for (rec, back) in recs.iter().zip(&back) {
+                for (a, b) in rec.matrix().iter().zip(back.matrix()) {
+                    assert!((a - b).abs() < 1e-5);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn parses_transfac() {
+        let text = "ID one\nXX\nP0 T G C A\n01 0 0 1 3\n02 2 2 0 0\nXX\n//\n";
+        let recs = transfac_from_str(text).unwrap();
+        assert_eq!(recs.len(), 1);
+        assert_cols(&recs[0], &[[0.75, 0.25, 0.0, 0.0], [0.0, 0.0, 0.5, 0.5]]);
+        assert!(transfac_from_str("P0 A C G\n01 1 1 1\n").is_err());
+        assert!(transfac_from_str("ID none\n//\n").is_err());
+    }
+}
@@ -1,14 +1,14 @@
 use crate::aux;
 use std::collections::VecDeque;
 #[derive(Debug, Default)]
-enum NodeType {
+pub enum NodeType {
     #[default]
     Recognizer,
     Connector,
 }
 
 #[derive(Debug, Default)]
-struct Node {
+pub struct Node {
     node_type: NodeType,
     start: usize,
     stop: usize,
@@ -17,7 +17,25 @@ struct Node {
 }
 
 impl Node {
-    pub fn node_type(&self) -> NodeType {}
+    pub fn node_type(&self) -> &NodeType {
+        &self.node_type
+    }
+
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    pub fn stop(&self) -> usize {
+        self.stop
+    }
+
+    pub fn energy(&self) -> f64 {
+        self.energy
+    }
+
+    pub fn seq(&self) -> &str {
+        &self.seq
+    }
 }
 
 #[derive(Debug, Default)]
@@ -26,23 +44,10 @@ pub struct Placement {
     nodes: VecDeque<Node>,
 }
 
-pub trait New {
-    fn new() -> Placement;
-}
-
 pub trait WithCapacity {
     fn with_capacity(len: usize) -> Placement;
 }
 
-impl New for Placement {
-    fn new() -> Placement {
-        Placement {
-            energy: 0.0,
-            nodes: VecDeque::new(),
-        }
-    }
-}
-
 impl WithCapacity for Placement {
     fn with_capacity(capacity: usize) -> Placement {
         Placement {
@@ -57,7 +62,7 @@ impl Placement {
         &self.nodes[idx]
     }
 
-    pub fn mut_at(&mut self, idx: usize) {
+    pub fn mut_at(&mut self, idx: usize) -> &mut Node {
         &mut self.nodes[idx]
     }
 
@@ -83,15 +88,15 @@ impl Placement {
     }
 
     pub fn from_matrix(
-        seq: &[char],
-        rs_matrix: &Vec<Vec<f64>>,
-        gs_matrix: &Vec<Vec<f64>>,
-        tr_matrix: &Vec<Vec<usize>>,
-        rec_lengths: &Vec<usize>,
-        c_row: &Vec<f64>,
+        seq: &[u8],
+        rs_matrix: &[Vec<f64>],
+        gs_matrix: &[Vec<f64>],
+        tr_matrix: &[Vec<usize>],
+        rec_lengths: &[usize],
+        c_row: &[f64],
         m_len: usize,
     ) -> Placement {
-        /* seq: array of characters representing the dna sequence */
+        /* seq: array of bytes representing the dna sequence */
         /* rs_matrix: 2d vector of recognizer scores from sliding recognizers along seq */
         /* gs_matrix: 2d vector of connector scores corresponding to gaps in the traceback matrix */
         /* tr_matrix: 2d vecotr of gap lengths showing traceback of best alignment */
@@ -99,7 +104,7 @@ impl Placement {
         /* c_row: the row of cumulative scores after running the place function */
         /* m_len: sum of the lengths of all the recognizers */
 
-        let m_idx: usize = aux::maxf_idx(&c_row);
+        let m_idx: usize = aux::maxf_idx(c_row);
         let mut new: Placement = Placement::with_capacity(rec_lengths.len());
         new.energy = c_row[m_idx];
         let mut c_idx = m_idx + m_len - 1;
@@ -114,7 +119,7 @@ impl Placement {
                 s_idx,
                 c_idx,
                 rs_matrix[idx][m_idx],
-                seq[s_idx..c_idx + 1].iter().collect(),
+                seq[s_idx..c_idx + 1].iter().map(|b| *b as char).collect(),
             );
 
             if i > 0 {
@@ -124,7 +129,7 @@ impl Placement {
                     c_idx,
                     s_idx,
                     gs_matrix[idx - 1][m_idx],
-                    seq[c_idx + 1..s_idx].iter().collect(),
+                    seq[c_idx + 1..s_idx].iter().map(|b| *b as char).collect(),
                 );
                 m_idx -= tr_matrix[idx - 1][m_idx];
             }
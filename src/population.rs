@@ -0,0 +1,184 @@
+use crate::dataset::Dataset;
+use crate::error::{FitnessError, OrganismError};
+use crate::factory::Factory;
+use crate::fitness::{self, Fitness};
+use crate::Organism;
+use rand::Rng;
+use std::thread;
+
+#[derive(Default, Debug, Clone)]
+pub struct Population {
+    orgs: Vec<Organism>,
+    fitness: Vec<f64>,
+}
+
+impl Population {
+    pub fn orgs(&self) -> &Vec<Organism> {
+        &self.orgs
+    }
+
+    pub fn orgs_mut(&mut self) -> &mut Vec<Organism> {
+        &mut self.orgs
+    }
+
+    pub fn org_at(&self, org_idx: usize) -> &Organism {
+        &self.orgs[org_idx]
+    }
+
+    pub fn fitness(&self) -> &Vec<f64> {
+        &self.fitness
+    }
+
+    pub fn fitness_at(&self, org_idx: usize) -> f64 {
+        self.fitness[org_idx]
+    }
+
+    pub fn len(&self) -> usize {
+        self.orgs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.orgs.is_empty()
+    }
+
+    pub fn is_evaluated(&self) -> bool {
+        self.fitness.len() == self.orgs.len()
+    }
+
+    pub fn best(&self) -> Option<(usize, f64)> {
+        self.fitness
+            .iter()
+            .copied()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    pub fn to_json_list(&self, org_file: &str) -> Result<(), OrganismError> {
+        crate::to_json_list(&self.orgs, org_file)
+    }
+
+    pub fn set_fitness(&mut self, fitness: Vec<f64>) {
+        self.fitness = fitness;
+    }
+
+    pub fn push(&mut self, org: Organism) {
+        self.orgs.push(org);
+        self.fitness.clear();
+    }
+
+    pub fn evaluate(
+        &mut self,
+        pos: &Dataset,
+        neg: &Dataset,
+        fitness: &dyn Fitness,
+    ) -> Result<(), FitnessError> {
+        /* organisms that cannot be placed on the datasets get the worst possible fitness */
+        self.fitness = Vec::with_capacity(self.orgs.len());
+        for org in self.orgs.iter_mut() {
+            self.fitness.push(score(org, pos, neg, fitness)?);
+        }
+        Ok(())
+    }
+
+    pub fn evaluate_parallel(
+        &mut self,
+        pos: &Dataset,
+        neg: &Dataset,
+        fitness: &dyn Fitness,
+        num_threads: usize,
+    ) -> Result<(), FitnessError> {
+        /* every worker scores a contiguous chunk of organisms, so the fitness vector */
+        /* ends up in the same order, with the same values, as the serial evaluation */
+        let num_threads = match num_threads {
+            0 => thread::available_parallelism().map_or(1, |n| n.get()),
+            n => n,
+        };
+        let chunk_size = self.orgs.len().div_ceil(num_threads).max(1);
+        let chunks: Vec<Result<Vec<f64>, FitnessError>> = thread::scope(|scope| {
+            let workers: Vec<_> = self
+                .orgs
+                .chunks_mut(chunk_size)
+                .map(|chunk| {
+                    scope.spawn(move || {
+                        chunk
+                            .iter_mut()
+                            .map(|org| score(org, pos, neg, fitness))
+                            .collect()
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .map(|worker| worker.join().expect("fitness worker panicked"))
+                .collect()
+        });
+
+        self.fitness = Vec::with_capacity(self.orgs.len());
+        for chunk in chunks {
+            self.fitness.extend(chunk?);
+        }
+        Ok(())
+    }
+}
+
+fn score(
+    org: &mut Organism,
+    pos: &Dataset,
+    neg: &Dataset,
+    fitness: &dyn Fitness,
+) -> Result<f64, FitnessError> {
+    match fitness::evaluate(org, pos, neg, fitness) {
+        Ok(score) if !score.is_nan() => Ok(score),
+        Ok(_) | Err(FitnessError::OrganismError(_)) => Ok(f64::NEG_INFINITY),
+        Err(e) => Err(e),
+    }
+}
+
+pub fn population(orgs: Vec<Organism>) -> Population {
+    Population {
+        orgs,
+        fitness: Vec::new(),
+    }
+}
+
+pub fn random(factory: &Factory, size: usize, rng: &mut impl Rng) -> Population {
+    population((0..size).map(|_| factory.organism(rng)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ConnectorConfig, FactoryConfig, OrganismConfig, RecognizerConfig};
+    use crate::dataset::{fasta_from_reader, TEST_POSITIVES};
+    use crate::factory::factory;
+    use crate::fitness::Welchs;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha12Rng;
+
+    #[test]
+    fn parallel_evaluation_matches_serial() {
+        let neg = ">n1\natatatatatatatatatatatatatatatat\n>n2\ngcgcgcgcgcgcgcgcgcgcgcgcgcgcgcgc\n\
+            >n3\naaaaccccggggttttaaaaccccggggtttt\n";
+        let pos = fasta_from_reader(TEST_POSITIVES.as_bytes(), "pos.fa").unwrap();
+        let neg = fasta_from_reader(neg.as_bytes(), "neg.fa").unwrap();
+        let factory = factory(
+            FactoryConfig::default(),
+            OrganismConfig::default(),
+            RecognizerConfig::default(),
+            ConnectorConfig::default(),
+        )
+        .unwrap();
+        let mut rng = ChaCha12Rng::seed_from_u64(11);
+        let mut serial = random(&factory, 13, &mut rng);
+        let mut parallel = serial.clone();
+
+        serial.evaluate(&pos, &neg, &Welchs).unwrap();
+        assert!(serial.fitness().iter().all(|fitness| fitness.is_finite()));
+        for num_threads in [1, 2, 4, 16] {
+            parallel
+                .evaluate_parallel(&pos, &neg, &Welchs, num_threads)
+                .unwrap();
+            assert_eq!(parallel.fitness(), serial.fitness());
+        }
+    }
+}
@@ -1,10 +1,22 @@
+use crate::aux::{self, JsonFloat};
 use crate::config::RecognizerConfig;
-use crate::error::RecognizerError;
+use crate::error::{ParseError, RecognizerError};
+use rand::Rng;
+use serde::{ser, Deserialize, Serialize, Serializer};
 use serde_json::Value;
+use std::collections::BTreeMap;
+use std::ops::Not;
+use std::sync::{Arc, Mutex};
 
 const BASES: [&str; 4] = ["a", "c", "g", "t"];
+const NULL_FREQS: [f64; 4] = [0.25; 4];
+const PVALUE_GRANULARITY: f64 = 1e-3;
+const PVALUE_REFINEMENTS: usize = 4;
+const PVALUE_MAX_CELLS: usize = 1 << 24;
+const PVALUE_TOLERANCE: f64 = 1e-6;
+pub(crate) const MAX_KMER_ORDER: usize = 8;
 
-#[derive(Default, Copy, Clone, Debug)]
+#[derive(Deserialize, Serialize, Default, Copy, Clone, Debug)]
 pub enum ShapeFeat {
     #[default]
     MGW,
@@ -19,22 +31,61 @@ pub enum RecognizerFeat {
     #[default]
     Sequence,
     Shape(ShapeFeat),
+    Kmer(usize),
     None,
 }
 
-#[derive(Default, Debug, Clone)]
+#[derive(Deserialize, Default, Debug, Clone)]
+#[serde(try_from = "RecognizerData")]
 pub struct Recognizer {
     feat: RecognizerFeat,
     len: usize,
     matrix: Vec<f64>,
+    log_odds: bool,
     mu: f64,
     sigma: f64,
     null: Vec<(f64, f64, f64)>,
     alt: Vec<(f64, f64, f64)>,
     config: Option<RecognizerConfig>,
+    dists: DistributionCache,
 }
 
 impl Recognizer {
+    pub fn from_sites(
+        sites: &[&str],
+        pseudo_count: f64,
+        background: Option<[f64; 4]>,
+    ) -> Result<Recognizer, RecognizerError> {
+        /* probabilities, or log2(p / background) log-odds when a background is given */
+        if !(pseudo_count >= 0.0 && pseudo_count.is_finite()) {
+            return Err(RecognizerError::PseudoCountError(pseudo_count));
+        }
+        if let Some(background) = background {
+            check_background(&background)?;
+        }
+        let len = sites
+            .first()
+            .ok_or(RecognizerError::EmptySitesError)?
+            .chars()
+            .count();
+        let mut counts: Vec<f64> = vec![0.00; len * 4];
+        for site in sites {
+            if site.chars().count() != len {
+                return Err(RecognizerError::SiteLengthError(site.to_string()));
+            }
+            for (i, base) in site.chars().enumerate() {
+                let j = base_idx(base).ok_or(RecognizerError::InvalidBaseError(base))?;
+                counts[i * 4 + j] += 1.0;
+            }
+        }
+
+        let mut rec = from_counts(&counts, sites.len(), pseudo_count);
+        if background.is_some() {
+            rec.to_pssm(background);
+        }
+        Ok(rec)
+    }
+
     pub fn feat(&self) -> RecognizerFeat {
         self.feat
     }
@@ -43,6 +94,29 @@ impl Recognizer {
         self.len
     }
 
+    pub fn log_odds(&self) -> bool {
+        self.log_odds
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn order(&self) -> usize {
+        match self.feat {
+            RecognizerFeat::Kmer(order) => order,
+            _ => 0,
+        }
+    }
+
+    pub fn col_size(&self) -> usize {
+        4_usize.pow(self.order() as u32 + 1)
+    }
+
+    pub fn num_cols(&self) -> usize {
+        self.len - self.order()
+    }
+
     pub fn matrix(&self) -> &Vec<f64> {
         self.matrix.as_ref()
     }
@@ -73,11 +147,49 @@ impl Recognizer {
             .expect("recognizer does not have a config")
     }
 
-    pub fn to_pssm(&mut self) {
-        let matrix = self.matrix_mut();
-        for i in matrix {
-            *i = i.log2() / 0.25_f64.log2();
+    pub fn to_pssm(&mut self, background: Option<[f64; 4]>) {
+        /* log-odds are log2(p / background), the background uniform when not given */
+        let null = self.kmer_background(background);
+        let col_size = self.col_size();
+        for col in self.matrix.chunks_mut(col_size) {
+            for (i, null) in col.iter_mut().zip(&null) {
+                *i = (*i / null).log2();
+            }
+        }
+        self.log_odds = true;
+    }
+
+    pub fn to_pwm(&mut self, background: Option<[f64; 4]>) {
+        /* inverse of to_pssm for the same background */
+        let null = self.kmer_background(background);
+        let col_size = self.col_size();
+        for col in self.matrix.chunks_mut(col_size) {
+            for (i, null) in col.iter_mut().zip(&null) {
+                *i = null * i.exp2();
+            }
+            let total: f64 = col.iter().sum();
+            for i in col.iter_mut() {
+                *i /= total;
+            }
+        }
+        self.log_odds = false;
+    }
+
+    fn kmer_background(&self, background: Option<[f64; 4]>) -> Vec<f64> {
+        /* a k-mer's background is the product of its bases' */
+        let background = background.unwrap_or(NULL_FREQS);
+        let mut null: Vec<f64> = vec![1.0];
+        for _ in 0..self.order() + 1 {
+            null = null
+                .iter()
+                .flat_map(|prob| background.iter().map(move |base| prob * base))
+                .collect();
         }
+        null
+    }
+
+    pub fn to_value(&self) -> Result<Value, RecognizerError> {
+        Ok(serde_json::to_value(RecognizerData::try_from(self)?)?)
     }
 
     pub fn set_feat(&mut self, feat: RecognizerFeat) {
@@ -88,8 +200,18 @@ impl Recognizer {
         self.len = len;
     }
 
+    pub fn set_log_odds(&mut self, log_odds: bool) {
+        self.log_odds = log_odds;
+    }
+
     pub fn set_at(&mut self, val: f64, index: (usize, usize)) {
-        self.matrix[index.0 * self.len + index.1] = val;
+        /* index is (column, row), rows being the 4^(k+1) k-mers of a column */
+        let col_size = self.col_size();
+        self.matrix[index.0 * col_size + index.1] = val;
+    }
+
+    pub fn set_config(&mut self, config: Option<RecognizerConfig>) {
+        self.config = config;
     }
 
     pub fn set_mu(&mut self, mu: f64) {
@@ -101,77 +223,170 @@ impl Recognizer {
     }
 
     pub fn print(&self) {
-        let len = self.len();
-        for i in 0..4 {
-            for j in 0..len {
-                print!("|{}", &self.matrix[j * 4 + i])
+        let num_cols = self.num_cols();
+        let col_size = self.col_size();
+        for i in 0..col_size {
+            for j in 0..num_cols {
+                print!("|{}", &self.matrix[j * col_size + i])
             }
-            println!("");
+            println!();
         }
     }
 
     pub fn flip_row(&mut self, row: usize) {
-        let len = self.len();
-        for i in 0..len / 2 {
-            self.matrix.swap((i * 4) + row, (len - i) * 4 - (4 - row));
+        let num_cols = self.num_cols();
+        let col_size = self.col_size();
+        for i in 0..num_cols / 2 {
+            self.matrix.swap(
+                (i * col_size) + row,
+                (num_cols - i) * col_size - (col_size - row),
+            );
         }
     }
 
     pub fn flip_col(&mut self, col: usize) {
-        for i in 0..2 {
-            self.matrix.swap(col * 4 + i, (col + 1) * 4 - 1 - i);
+        /* swaps every k-mer with its reverse complement; palindromes stay in place */
+        let col_size = self.col_size();
+        let kmer_len = self.order() + 1;
+        for i in 0..col_size {
+            let j = revcomp_idx(i, kmer_len);
+            if i < j {
+                self.matrix.swap(col * col_size + i, col * col_size + j);
+            }
         }
     }
 
     pub fn swap_cols(&mut self, col_a: usize, col_b: usize) {
-        for i in 0..4 {
-            self.matrix.swap(col_a * 4 + i, col_b * 4 + i)
+        let col_size = self.col_size();
+        for i in 0..col_size {
+            self.matrix.swap(col_a * col_size + i, col_b * col_size + i)
         }
     }
 
     pub fn swap_rows(&mut self, row_a: usize, row_b: usize) {
-        let len = self.len();
-        for i in 0..len {
-            self.matrix.swap(row_a + i * 4, row_b + i * 4)
+        let num_cols = self.num_cols();
+        let col_size = self.col_size();
+        for i in 0..num_cols {
+            self.matrix.swap(row_a + i * col_size, row_b + i * col_size)
         }
     }
 
     pub fn shift_left(&mut self) {
-        let len = self.len();
-        for i in 0..len - 1 {
+        let num_cols = self.num_cols();
+        for i in 0..num_cols - 1 {
             self.swap_cols(i, i + 1);
         }
     }
 
     pub fn shift_right(&mut self) {
-        let len = self.len();
-        for i in len - 1..0 {
+        let num_cols = self.num_cols();
+        for i in (1..num_cols).rev() {
             self.swap_cols(i, i - 1);
         }
     }
 
-    pub fn calculate_row(&self, seq: &[char], row: &mut Vec<f64>) {
+    pub fn random_col(&mut self, col: usize, rng: &mut impl Rng) {
+        /* columns are log-odds against a uniform background, as the factory builds them */
+        let col_size = self.col_size();
+        let vals: Vec<f64> = (0..col_size).map(|_| rng.gen::<f64>()).collect();
+        let total: f64 = vals.iter().sum();
+        for (i, val) in vals.iter().enumerate() {
+            self.matrix[col * col_size + i] = (val / total * col_size as f64).log2();
+        }
+    }
+
+    pub fn mutate_col(&mut self, col: usize, rng: &mut impl Rng) {
+        /* moves a random share of one entry's probability onto another so the column */
+        /* stays normalised */
+        let col_size = self.col_size();
+        let from = col * col_size + rng.gen_range(0..col_size);
+        let to = col * col_size + rng.gen_range(0..col_size);
+        let prob = |score: f64| score.exp2() / col_size as f64;
+        let amount = prob(self.matrix[from]) * rng.gen::<f64>();
+        self.matrix[from] = ((prob(self.matrix[from]) - amount) * col_size as f64).log2();
+        self.matrix[to] = ((prob(self.matrix[to]) + amount) * col_size as f64).log2();
+    }
+
+    pub fn push_col(&mut self, rng: &mut impl Rng) {
+        let col_size = self.col_size();
+        let col = self.num_cols();
+        self.matrix.resize((col + 1) * col_size, 0.0);
+        self.len += 1;
+        self.random_col(col, rng);
+    }
+
+    pub fn pop_col(&mut self) {
+        let col_size = self.col_size();
+        self.matrix.truncate((self.num_cols() - 1) * col_size);
+        self.len -= 1;
+    }
+
+    pub fn mutate(&mut self, rng: &mut impl Rng) {
+        match self.feat {
+            RecognizerFeat::Sequence | RecognizerFeat::Kmer(_) => {}
+            _ => return,
+        }
+        let conf = match &self.config {
+            Some(conf) => conf.clone(),
+            None => return,
+        };
+
+        if rng.gen::<f64>() < conf.mutate_probability_random_col() {
+            let col = rng.gen_range(0..self.num_cols());
+            self.random_col(col, rng);
+        }
+        if rng.gen::<f64>() < conf.mutate_probability_mutate_col() {
+            let col = rng.gen_range(0..self.num_cols());
+            self.mutate_col(col, rng);
+        }
+        if rng.gen::<f64>() < conf.mutate_probability_flip_col() {
+            self.flip_col(rng.gen_range(0..self.num_cols()));
+        }
+        if rng.gen::<f64>() < conf.mutate_probability_flip_row() {
+            self.flip_row(rng.gen_range(0..self.col_size()));
+        }
+        if rng.gen::<f64>() < conf.mutate_probability_shift_left() {
+            self.shift_left();
+        }
+        if rng.gen::<f64>() < conf.mutate_probability_shift_right() {
+            self.shift_right();
+        }
+        if rng.gen::<f64>() < conf.mutate_probability_increase_pwm()
+            && self.len < conf.max_columns()
+        {
+            self.push_col(rng);
+        }
+        if rng.gen::<f64>() < conf.mutate_probability_decrease_pwm()
+            && self.len > conf.min_columns()
+            && self.num_cols() > 1
+        {
+            self.pop_col();
+        }
+    }
+
+    pub fn calculate_row(&self, seq: &[u8], row: &mut [f64]) {
         match self.feat {
             RecognizerFeat::Sequence => self.pssm_row(seq, row),
+            RecognizerFeat::Kmer(_) => self.kmer_row(seq, row),
             _ => self.shape_row(seq, row),
         }
     }
 
-    fn pssm_row(&self, seq: &[char], row: &mut Vec<f64>) {
+    fn pssm_row(&self, seq: &[u8], row: &mut [f64]) {
         let t_scores = self.matrix();
 
         for i in 0..seq.len() - self.len + 1 {
             let mut score = 0.00;
             for j in 0..self.len() {
                 match seq[i + j] {
-                    'a' => score += t_scores[j * 4 + 0],
-                    'A' => score += t_scores[j * 4 + 0],
-                    'c' => score += t_scores[j * 4 + 1],
-                    'C' => score += t_scores[j * 4 + 1],
-                    'g' => score += t_scores[j * 4 + 2],
-                    'G' => score += t_scores[j * 4 + 2],
-                    't' => score += t_scores[j * 4 + 3],
-                    'T' => score += t_scores[j * 4 + 3],
+                    b'a' => score += t_scores[j * 4],
+                    b'A' => score += t_scores[j * 4],
+                    b'c' => score += t_scores[j * 4 + 1],
+                    b'C' => score += t_scores[j * 4 + 1],
+                    b'g' => score += t_scores[j * 4 + 2],
+                    b'G' => score += t_scores[j * 4 + 2],
+                    b't' => score += t_scores[j * 4 + 3],
+                    b'T' => score += t_scores[j * 4 + 3],
                     _ => break,
                 }
             }
@@ -179,24 +394,433 @@ impl Recognizer {
         }
     }
 
-    fn shape_row(&self, seq: &[char], row: &mut Vec<f64>) {}
+    fn kmer_row(&self, seq: &[u8], row: &mut [f64]) {
+        let t_scores = self.matrix();
+        let order = self.order();
+        let col_size = self.col_size();
+
+        for i in 0..seq.len() - self.len + 1 {
+            let mut score = 0.00;
+            for j in 0..self.num_cols() {
+                match kmer_idx(&seq[i + j..i + j + order + 1]) {
+                    Some(idx) => score += t_scores[j * col_size + idx],
+                    None => break,
+                }
+            }
+            row[i] = score;
+        }
+    }
+
+    fn shape_row(&self, _seq: &[u8], _row: &mut [f64]) {}
+
+    pub fn max_score(&self) -> f64 {
+        match self.feat {
+            RecognizerFeat::Sequence | RecognizerFeat::Kmer(_) => {}
+            _ => return 0.0,
+        }
+
+        let col_size = self.col_size();
+        let num_states = col_size / 4;
+        let mut best: Vec<f64> = vec![f64::NEG_INFINITY; num_states];
+        for (kmer, score) in self.matrix[..col_size].iter().enumerate() {
+            best[kmer % num_states] = best[kmer % num_states].max(*score);
+        }
+        for col in self.matrix.chunks(col_size).skip(1) {
+            let mut next: Vec<f64> = vec![f64::NEG_INFINITY; num_states];
+            for (kmer, score) in col.iter().enumerate() {
+                let state = kmer / 4;
+                next[kmer % num_states] = next[kmer % num_states].max(best[state] + score);
+            }
+            best = next;
+        }
+        best.into_iter().fold(f64::NEG_INFINITY, f64::max)
+    }
+
+    pub fn score_pvalue(&self, score: f64) -> Result<f64, RecognizerError> {
+        /* TFM-PVALUE style: the rounded score D satisfies D <= S < D + error, so */
+        /* P(D >= score) <= P(S >= score) <= P(D >= score - error); refine the */
+        /* granularity until both bounds agree, else return the upper bound */
+        let mut pvalue: f64 = 1.0;
+        for level in 0..PVALUE_REFINEMENTS {
+            let dist = match self.score_distribution(level)? {
+                Some(dist) => dist,
+                None => break,
+            };
+            let lower = dist.first_bin(score);
+            let upper = dist.first_bin(score - dist.error);
+            pvalue = dist.tail(upper);
+            if dist.is_empty_between(upper, lower) {
+                return Ok(dist.tail(lower));
+            }
+        }
+        Ok(pvalue)
+    }
+
+    pub fn threshold_for_pvalue(&self, pvalue: f64) -> Result<f64, RecognizerError> {
+        /* lowest rounded score whose tail is within pvalue; exact once no rounded */
+        /* score below it can reach it, otherwise shifted up by the error bound */
+        let mut threshold: f64 = f64::INFINITY;
+        for level in 0..PVALUE_REFINEMENTS {
+            let dist = match self.score_distribution(level)? {
+                Some(dist) => dist,
+                None => break,
+            };
+            let mut tail: f64 = 0.0;
+            let mut bin = dist.probs.len();
+            while bin > 0 && tail + dist.probs[bin - 1] <= pvalue {
+                tail += dist.probs[bin - 1];
+                bin -= 1;
+            }
+            let score = dist.score(bin);
+            threshold = score + dist.error;
+            if dist.is_empty_between(dist.first_bin(score - dist.error), bin) {
+                return Ok(score);
+            }
+        }
+        Ok(threshold)
+    }
+
+    fn score_distribution(
+        &self,
+        level: usize,
+    ) -> Result<Option<Arc<ScoreDistribution>>, RecognizerError> {
+        /* cached per recognizer and per refinement level, keyed on the matrix */
+        match self.feat {
+            RecognizerFeat::Sequence | RecognizerFeat::Kmer(_) => {}
+            _ => return Err(RecognizerError::UnsupportedFeatError(self.feat)),
+        }
+
+        let mut cache = self.dists.0.lock().unwrap_or_else(|e| e.into_inner());
+        if cache.matrix != self.matrix || cache.levels.len() != PVALUE_REFINEMENTS {
+            *cache = Distributions {
+                matrix: self.matrix.clone(),
+                levels: vec![None; PVALUE_REFINEMENTS],
+            };
+        }
+        if cache.levels[level].is_none() {
+            let granularity = PVALUE_GRANULARITY / 10_f64.powi(level as i32);
+            cache.levels[level] = self
+                .build_distribution(granularity, level == 0)
+                .map(Arc::new);
+        }
+        Ok(cache.levels[level].clone())
+    }
+
+    fn build_distribution(&self, granularity: f64, force: bool) -> Option<ScoreDistribution> {
+        /* dynamic programming over columns with scores floored to granularity, */
+        /* tracking the last k bases as state so order-k columns stay exact */
+        /* gives up when the table would exceed PVALUE_MAX_CELLS unless forced */
+        let col_size = self.col_size();
+        let num_states = col_size / 4;
+        let empty = ScoreDistribution {
+            min_score: 0.0,
+            granularity,
+            error: 0.0,
+            probs: vec![0.0],
+        };
+        if col_size == 0 || self.matrix.is_empty() {
+            return Some(empty);
+        }
+
+        let mut min_score: f64 = 0.0;
+        let mut max_score: f64 = 0.0;
+        for col in self.matrix.chunks(col_size) {
+            let finite = col.iter().filter(|v| v.is_finite());
+            let col_min = finite.clone().fold(f64::INFINITY, |a, b| a.min(*b));
+            let col_max = finite.fold(f64::NEG_INFINITY, |a, b| a.max(*b));
+            if !col_min.is_finite() {
+                return Some(empty);
+            }
+            min_score += col_min;
+            max_score += col_max;
+        }
+        let num_bins = ((max_score - min_score) / granularity).ceil() as usize + 1;
+        if !force && num_bins.saturating_mul(num_states) > PVALUE_MAX_CELLS {
+            return None;
+        }
+
+        let mut error: f64 = 0.0;
+        let mut bins: Vec<Option<usize>> = vec![None; self.matrix.len()];
+        for (i, col) in self.matrix.chunks(col_size).enumerate() {
+            let col_min = col
+                .iter()
+                .filter(|v| v.is_finite())
+                .fold(f64::INFINITY, |a, b| a.min(*b));
+            let mut col_error: f64 = 0.0;
+            for (j, score) in col.iter().enumerate() {
+                if score.is_finite() {
+                    let offset = (score - col_min) / granularity;
+                    let bin = (offset + PVALUE_TOLERANCE).floor();
+                    if offset - bin > PVALUE_TOLERANCE {
+                        col_error = col_error.max((offset - bin) * granularity);
+                    }
+                    bins[i * col_size + j] = Some((bin as usize).min(num_bins - 1));
+                }
+            }
+            error += col_error;
+        }
+
+        let mut dist: Vec<Vec<f64>> = vec![vec![0.0; num_bins]; num_states];
+        for (kmer, bin) in bins[..col_size].iter().enumerate() {
+            if let Some(bin) = bin {
+                let mut prob: f64 = 1.0;
+                let mut rest = kmer;
+                for _ in 0..self.order() + 1 {
+                    prob *= NULL_FREQS[rest % 4];
+                    rest /= 4;
+                }
+                dist[kmer % num_states][*bin] += prob;
+            }
+        }
+
+        for col in bins.chunks(col_size).skip(1) {
+            let mut next: Vec<Vec<f64>> = vec![vec![0.0; num_bins]; num_states];
+            for (kmer, shift) in col.iter().enumerate() {
+                let shift = match shift {
+                    Some(shift) => *shift,
+                    None => continue,
+                };
+                let (state, base) = (kmer / 4, kmer % 4);
+                let target = &mut next[kmer % num_states];
+                for (bin, prob) in dist[state][..num_bins - shift].iter().enumerate() {
+                    target[bin + shift] += prob * NULL_FREQS[base];
+                }
+            }
+            dist = next;
+        }
+
+        let mut probs: Vec<f64> = vec![0.0; num_bins];
+        for state in dist {
+            for (total, prob) in probs.iter_mut().zip(state) {
+                *total += prob;
+            }
+        }
+        Some(ScoreDistribution {
+            min_score,
+            granularity,
+            error,
+            probs,
+        })
+    }
+}
+
+#[derive(Debug)]
+struct ScoreDistribution {
+    min_score: f64,
+    granularity: f64,
+    error: f64,
+    probs: Vec<f64>,
+}
+
+impl ScoreDistribution {
+    fn score(&self, bin: usize) -> f64 {
+        self.min_score + bin as f64 * self.granularity
+    }
+
+    fn first_bin(&self, score: f64) -> usize {
+        /* first bin whose rounded score reaches score */
+        let bin = ((score - self.min_score) / self.granularity - PVALUE_TOLERANCE).ceil();
+        if bin <= 0.0 {
+            return 0;
+        }
+        (bin as usize).min(self.probs.len())
+    }
+
+    fn tail(&self, bin: usize) -> f64 {
+        self.probs[bin..].iter().sum()
+    }
+
+    fn is_empty_between(&self, from: usize, to: usize) -> bool {
+        from >= to || self.probs[from..to].iter().all(|prob| *prob == 0.0)
+    }
+}
+
+#[derive(Default, Debug, Clone)]
+struct Distributions {
+    matrix: Vec<f64>,
+    levels: Vec<Option<Arc<ScoreDistribution>>>,
+}
+
+#[derive(Default, Debug)]
+struct DistributionCache(Mutex<Distributions>);
+
+impl Clone for DistributionCache {
+    fn clone(&self) -> Self {
+        let cache = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        DistributionCache(Mutex::new(cache.clone()))
+    }
+}
+
+/* the serialized form of a recognizer, one pwm column per map of k-mer to score; */
+/* the scores are probabilities as in organism.json unless logOdds is set */
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(tag = "objectType", rename_all = "lowercase")]
+pub(crate) enum RecognizerData {
+    Pssm {
+        #[serde(rename = "logOdds", default, skip_serializing_if = "Not::not")]
+        log_odds: bool,
+        pwm: Vec<BTreeMap<String, JsonFloat>>,
+    },
+    Kpssm {
+        order: usize,
+        #[serde(rename = "logOdds", default, skip_serializing_if = "Not::not")]
+        log_odds: bool,
+        pwm: Vec<BTreeMap<String, JsonFloat>>,
+    },
+    Shape {
+        feature: ShapeFeat,
+        mu: f64,
+        sigma: f64,
+        length: usize,
+    },
+}
+
+impl TryFrom<&Recognizer> for RecognizerData {
+    type Error = RecognizerError;
+
+    fn try_from(rec: &Recognizer) -> Result<RecognizerData, RecognizerError> {
+        let pwm = || -> Vec<BTreeMap<String, JsonFloat>> {
+            let labels = kmers(rec.order() + 1);
+            rec.matrix
+                .chunks(rec.col_size())
+                .map(|col| {
+                    labels
+                        .iter()
+                        .cloned()
+                        .zip(col.iter().map(|val| JsonFloat::from(*val)))
+                        .collect()
+                })
+                .collect()
+        };
+        let log_odds = rec.log_odds;
+        match rec.feat {
+            RecognizerFeat::Sequence => Ok(RecognizerData::Pssm {
+                log_odds,
+                pwm: pwm(),
+            }),
+            RecognizerFeat::Kmer(order) => Ok(RecognizerData::Kpssm {
+                order,
+                log_odds,
+                pwm: pwm(),
+            }),
+            RecognizerFeat::Shape(feature) => Ok(RecognizerData::Shape {
+                feature,
+                mu: rec.mu,
+                sigma: rec.sigma,
+                length: rec.len,
+            }),
+            RecognizerFeat::None => Err(RecognizerError::UnsupportedFeatError(rec.feat)),
+        }
+    }
+}
+
+impl TryFrom<RecognizerData> for Recognizer {
+    type Error = RecognizerError;
+
+    fn try_from(data: RecognizerData) -> Result<Recognizer, RecognizerError> {
+        /* comes without a config, attach one with set_config */
+        match data {
+            RecognizerData::Pssm { log_odds, pwm } => {
+                let mut rec = pssm(
+                    RecognizerFeat::Sequence,
+                    pwm.len(),
+                    Some(matrix_from_cols(&pwm, 0)?),
+                    None,
+                );
+                rec.log_odds = log_odds;
+                Ok(rec)
+            }
+            RecognizerData::Kpssm {
+                order,
+                log_odds,
+                pwm,
+            } => {
+                if order > MAX_KMER_ORDER {
+                    return Err(ParseError::new(
+                        ".order",
+                        &format!("order of at most {}", MAX_KMER_ORDER),
+                    )
+                    .into());
+                }
+                let mut rec = pssm(
+                    RecognizerFeat::Kmer(order),
+                    pwm.len() + order,
+                    Some(matrix_from_cols(&pwm, order)?),
+                    None,
+                );
+                rec.log_odds = log_odds;
+                Ok(rec)
+            }
+            RecognizerData::Shape {
+                feature,
+                mu,
+                sigma,
+                length,
+            } => {
+                if let ShapeFeat::None = feature {
+                    return Err(ParseError::new(".feature", "one of MGW, ProT, HelT, Roll").into());
+                }
+                if sigma.is_nan() || sigma <= 0.0 {
+                    return Err(ParseError::new(".sigma", "positive number").into());
+                }
+                if length == 0 {
+                    return Err(ParseError::new(".length", "positive integer").into());
+                }
+                Ok(shape(
+                    RecognizerFeat::Shape(feature),
+                    length,
+                    Some(mu),
+                    Some(sigma),
+                    None,
+                ))
+            }
+        }
+    }
+}
+
+impl Serialize for Recognizer {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        RecognizerData::try_from(self)
+            .map_err(ser::Error::custom)?
+            .serialize(serializer)
+    }
+}
+
+fn matrix_from_cols(
+    cols: &[BTreeMap<String, JsonFloat>],
+    order: usize,
+) -> Result<Vec<f64>, ParseError> {
+    if cols.is_empty() {
+        return Err(ParseError::new(".pwm", "non-empty array"));
+    }
+    let labels = kmers(order + 1);
+    let mut matrix: Vec<f64> = Vec::with_capacity(cols.len() * labels.len());
+    for (i, col) in cols.iter().enumerate() {
+        for label in &labels {
+            let val = col
+                .get(label)
+                .and_then(|val| val.value())
+                .ok_or_else(|| ParseError::new(&format!(".pwm[{}].{}", i, label), "number"))?;
+            matrix.push(val);
+        }
+    }
+    Ok(matrix)
 }
 
 pub fn from_value(
     rec: &Value,
     conf: Option<&RecognizerConfig>,
 ) -> Result<Recognizer, RecognizerError> {
-    match rec.as_object().unwrap()["objectType"]
-        .as_str()
-        .ok_or_else(|| {
-            RecognizerError::ParseJSONError(serde::de::Error::invalid_type(
-                serde::de::Unexpected::Option,
-                &"hi",
-            ))
-        })? {
-        "pssm" => pssm_from_value(&rec["pwm"], conf),
-        "shape" => shape_from_value(&rec, conf),
-        _ => Err(RecognizerError::LoadRecognizerError),
+    let obj = aux::as_object(rec, "")?;
+    match aux::as_str(aux::field(obj, "objectType"), ".objectType")? {
+        "pssm" => {
+            let log_odds = aux::as_bool(aux::field(obj, "logOdds"), ".logOdds")?;
+            let pwm = cols_from_value(aux::field(obj, "pwm"), 0)?;
+            with_config(RecognizerData::Pssm { log_odds, pwm }, conf)
+        }
+        "shape" => shape_from_value(rec, conf),
+        "kpssm" => kpssm_from_value(rec, conf),
+        _ => Err(ParseError::new(".objectType", "one of pssm, kpssm, shape").into()),
     }
 }
 
@@ -204,75 +828,141 @@ pub fn pssm_from_value(
     rec: &Value,
     conf: Option<&RecognizerConfig>,
 ) -> Result<Recognizer, RecognizerError> {
-    let rec = rec.as_array().ok_or_else(|| {
-        RecognizerError::ParseJSONError(serde::de::Error::invalid_type(
-            serde::de::Unexpected::Option,
-            &"hi",
-        ))
-    })?;
-    let len = rec.len();
-    let mut matrix: Vec<f64> = vec![0.00; len * 4];
-    for i in 0..len {
-        let col = &rec[i].as_object().ok_or_else(|| {
-            RecognizerError::ParseJSONError(serde::de::Error::invalid_type(
-                serde::de::Unexpected::Option,
-                &"hi",
-            ))
-        })?;
-        for j in 0..BASES.len() {
-            matrix[i * 4 + j] = col[BASES[j]].as_f64().ok_or_else(|| {
-                RecognizerError::ParseJSONError(serde::de::Error::invalid_type(
-                    serde::de::Unexpected::Option,
-                    &"hi",
-                ))
-            })?;
-        }
+    /* rec is the pwm array of probabilities */
+    let pwm = cols_from_value(rec, 0)?;
+    with_config(
+        RecognizerData::Pssm {
+            log_odds: false,
+            pwm,
+        },
+        conf,
+    )
+}
+
+pub fn kpssm_from_value(
+    rec: &Value,
+    conf: Option<&RecognizerConfig>,
+) -> Result<Recognizer, RecognizerError> {
+    let obj = aux::as_object(rec, "")?;
+    let order = aux::as_usize(aux::field(obj, "order"), ".order")?;
+    if order > MAX_KMER_ORDER {
+        return Err(
+            ParseError::new(".order", &format!("order of at most {}", MAX_KMER_ORDER)).into(),
+        );
     }
-    Ok(pssm(
-        RecognizerFeat::Sequence,
-        len,
-        Some(matrix),
-        conf.cloned(),
-    ))
+    let log_odds = aux::as_bool(aux::field(obj, "logOdds"), ".logOdds")?;
+    let pwm = cols_from_value(aux::field(obj, "pwm"), order)?;
+    with_config(
+        RecognizerData::Kpssm {
+            order,
+            log_odds,
+            pwm,
+        },
+        conf,
+    )
 }
 
 pub fn shape_from_value(
     rec: &Value,
     conf: Option<&RecognizerConfig>,
 ) -> Result<Recognizer, RecognizerError> {
-    let feat = match rec["feature"].as_str().ok_or_else(|| {
-        RecognizerError::ParseJSONError(serde::de::Error::invalid_type(
-            serde::de::Unexpected::Option,
-            &"hi",
-        ))
-    })? {
-        "MGW" => RecognizerFeat::Shape(ShapeFeat::MGW),
-        "ProT" => RecognizerFeat::Shape(ShapeFeat::ProT),
-        "HelT" => RecognizerFeat::Shape(ShapeFeat::HelT),
-        "Roll" => RecognizerFeat::Shape(ShapeFeat::Roll),
-        _ => RecognizerFeat::Shape(ShapeFeat::None),
+    let obj = aux::as_object(rec, "")?;
+    let feature = match aux::as_str(aux::field(obj, "feature"), ".feature")? {
+        "MGW" => ShapeFeat::MGW,
+        "ProT" => ShapeFeat::ProT,
+        "HelT" => ShapeFeat::HelT,
+        "Roll" => ShapeFeat::Roll,
+        _ => {
+            return Err(ParseError::new(".feature", "one of MGW, ProT, HelT, Roll").into());
+        }
     };
+    let data = RecognizerData::Shape {
+        feature,
+        mu: aux::as_f64(aux::field(obj, "mu"), ".mu")?,
+        sigma: aux::as_f64(aux::field(obj, "sigma"), ".sigma")?,
+        length: aux::as_usize(aux::field(obj, "length"), ".length")?,
+    };
+    with_config(data, conf)
+}
+
+fn cols_from_value(
+    pwm: &Value,
+    order: usize,
+) -> Result<Vec<BTreeMap<String, JsonFloat>>, ParseError> {
+    /* only type checks here, with the path of each entry; the rest is left to TryFrom */
+    let labels = kmers(order + 1);
+    let mut cols: Vec<BTreeMap<String, JsonFloat>> = Vec::new();
+    for (i, col) in aux::as_array(pwm, ".pwm")?.iter().enumerate() {
+        let path = format!(".pwm[{}]", i);
+        let col = aux::as_object(col, &path)?;
+        let mut entries: BTreeMap<String, JsonFloat> = BTreeMap::new();
+        for label in &labels {
+            let val = aux::as_float(aux::field(col, label), &format!("{}.{}", path, label))?;
+            entries.insert(label.clone(), JsonFloat::from(val));
+        }
+        cols.push(entries);
+    }
+    Ok(cols)
+}
+
+fn with_config(
+    data: RecognizerData,
+    conf: Option<&RecognizerConfig>,
+) -> Result<Recognizer, RecognizerError> {
+    let mut rec = Recognizer::try_from(data)?;
+    rec.set_config(conf.cloned());
+    Ok(rec)
+}
+
+fn check_background(background: &[f64; 4]) -> Result<(), RecognizerError> {
+    let total: f64 = background.iter().sum();
+    if background.iter().any(|prob| prob.is_nan() || *prob <= 0.0) || (total - 1.0).abs() > 1e-6 {
+        return Err(RecognizerError::BackgroundError(background.to_vec()));
+    }
+    Ok(())
+}
 
-    let mu = rec["mu"].as_f64().ok_or_else(|| {
-        RecognizerError::ParseJSONError(serde::de::Error::invalid_type(
-            serde::de::Unexpected::Option,
-            &"hi",
-        ))
-    })?;
-    let sigma = rec["sigma"].as_f64().ok_or_else(|| {
-        RecognizerError::ParseJSONError(serde::de::Error::invalid_type(
-            serde::de::Unexpected::Option,
-            &"hi",
-        ))
-    })?;
-    let len = rec["length"].as_u64().ok_or_else(|| {
-        RecognizerError::ParseJSONError(serde::de::Error::invalid_type(
-            serde::de::Unexpected::Option,
-            &"hi",
-        ))
-    })? as usize;
-
-    Ok(shape(feat, len, Some(mu), Some(sigma), conf.cloned()))
+fn base_idx(base: char) -> Option<usize> {
+    match base {
+        'a' | 'A' => Some(0),
+        'c' | 'C' => Some(1),
+        'g' | 'G' => Some(2),
+        't' | 'T' => Some(3),
+        _ => None,
+    }
+}
+
+fn kmer_idx(kmer: &[u8]) -> Option<usize> {
+    let mut idx: usize = 0;
+    for base in kmer {
+        idx = idx * 4 + base_idx(*base as char)?;
+    }
+    Some(idx)
+}
+
+fn revcomp_idx(idx: usize, kmer_len: usize) -> usize {
+    /* a k-mer index holds one base per 2 bits, a=0 .. t=3, so complement is 3 - base */
+    let mut idx = idx;
+    let mut rc: usize = 0;
+    for _ in 0..kmer_len {
+        rc = rc * 4 + (3 - idx % 4);
+        idx /= 4;
+    }
+    rc
+}
+
+fn kmers(k: usize) -> Vec<String> {
+    let mut kmers: Vec<String> = vec![String::new()];
+    for _ in 0..k {
+        let mut next: Vec<String> = Vec::with_capacity(kmers.len() * 4);
+        for kmer in &kmers {
+            for base in BASES {
+                next.push(format!("{}{}", kmer, base));
+            }
+        }
+        kmers = next;
+    }
+    kmers
 }
 
 pub fn shape(
@@ -286,22 +976,31 @@ pub fn shape(
         feat,
         len,
         matrix: Vec::new(),
-        mu: if mu.is_some() {
-            mu.unwrap()
-        } else {
-            f64::default()
-        },
-        sigma: if sigma.is_some() {
-            sigma.unwrap()
-        } else {
-            f64::default()
-        },
+        log_odds: false,
+        mu: mu.unwrap_or_default(),
+        sigma: sigma.unwrap_or_default(),
         null: Vec::new(),
         alt: Vec::new(),
         config,
+        dists: DistributionCache::default(),
     }
 }
 
+pub(crate) fn from_counts(counts: &[f64], num_sites: usize, pseudo_count: f64) -> Recognizer {
+    /* probabilities from per column base counts; pseudo_count must already be valid */
+    let total = num_sites as f64 + 4.0 * pseudo_count;
+    let matrix: Vec<f64> = counts
+        .iter()
+        .map(|count| (count + pseudo_count) / total)
+        .collect();
+    pssm(
+        RecognizerFeat::Sequence,
+        counts.len() / 4,
+        Some(matrix),
+        None,
+    )
+}
+
 pub fn pssm(
     feat: RecognizerFeat,
     len: usize,
@@ -311,15 +1010,190 @@ pub fn pssm(
     Recognizer {
         feat,
         len,
-        matrix: if matrix.is_some() {
-            matrix.unwrap()
-        } else {
-            Vec::new()
-        },
+        matrix: matrix.unwrap_or_default(),
+        log_odds: false,
         mu: f64::default(),
         sigma: f64::default(),
         null: Vec::new(),
         alt: Vec::new(),
         config,
+        dists: DistributionCache::default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kmer_rec() -> Recognizer {
+        let matrix: Vec<f64> = (0..32).map(|i| i as f64).collect();
+        pssm(RecognizerFeat::Kmer(1), 3, Some(matrix), None)
+    }
+
+    #[test]
+    fn from_sites_log_odds_match_to_pssm() {
+        let sites = ["acgt", "acga", "tcgt"];
+        let background = [0.3, 0.2, 0.2, 0.3];
+        let mut probs = Recognizer::from_sites(&sites, 0.5, None).unwrap();
+        let log_odds = Recognizer::from_sites(&sites, 0.5, Some(background)).unwrap();
+        assert!((probs.matrix()[0] - 2.5 / 5.0).abs() < 1e-12);
+        assert!((log_odds.matrix()[0] - (0.5_f64 / 0.3).log2()).abs() < 1e-12);
+
+        let orig = probs.matrix().clone();
+        probs.to_pssm(Some(background));
+        assert_eq!(probs.matrix(), log_odds.matrix());
+        probs.to_pwm(Some(background));
+        for (a, b) in probs.matrix().iter().zip(&orig) {
+            assert!((a - b).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn json_keeps_log_odds_and_non_finite_scores() {
+        let rec = Recognizer::from_sites(&["acgt", "acgt"], 0.0, Some([0.25; 4])).unwrap();
+        assert_eq!(rec.matrix()[1], f64::NEG_INFINITY);
+        let value = rec.to_value().unwrap();
+        assert_eq!(value["logOdds"], true);
+        assert_eq!(value["pwm"][0]["c"], "-inf");
+
+        let read = from_value(&value, None).unwrap();
+        let json: Recognizer = serde_json::from_str(&value.to_string()).unwrap();
+        for read in [read, json] {
+            assert!(read.log_odds());
+            assert_eq!(read.matrix(), rec.matrix());
+        }
+
+        let probs = Recognizer::from_sites(&["acgt", "acgt"], 0.0, None).unwrap();
+        let value = probs.to_value().unwrap();
+        assert!(value.get("logOdds").is_none());
+        assert!(!from_value(&value, None).unwrap().log_odds());
+    }
+
+    #[test]
+    fn from_sites_rejects_bad_input() {
+        assert!(matches!(
+            Recognizer::from_sites(&["acgn"], 0.1, None),
+            Err(RecognizerError::InvalidBaseError('n'))
+        ));
+        assert!(matches!(
+            Recognizer::from_sites(&["acg", "acgt"], 0.1, None),
+            Err(RecognizerError::SiteLengthError(_))
+        ));
+        assert!(matches!(
+            Recognizer::from_sites(&["acgt"], -1.0, None),
+            Err(RecognizerError::PseudoCountError(_))
+        ));
+        assert!(matches!(
+            Recognizer::from_sites(&["acgt"], 0.1, Some([0.5, 0.5, 0.5, 0.5])),
+            Err(RecognizerError::BackgroundError(_))
+        ));
+    }
+
+    #[test]
+    fn set_at_indexes_kmer_columns() {
+        let mut rec = kmer_rec();
+        rec.set_at(-1.0, (1, 5));
+        assert_eq!(rec.matrix()[16 + 5], -1.0);
+        assert_eq!(rec.matrix().iter().filter(|val| **val < 0.0).count(), 1);
+    }
+
+    #[test]
+    fn flip_col_reverse_complements_kmers() {
+        let mut rec = kmer_rec();
+        rec.flip_col(0);
+        /* ac <-> gt, ag <-> ct, while the palindrome at stays put */
+        assert_eq!(rec.matrix()[1], 11.0);
+        assert_eq!(rec.matrix()[11], 1.0);
+        assert_eq!(rec.matrix()[2], 7.0);
+        assert_eq!(rec.matrix()[3], 3.0);
+        assert_eq!(&rec.matrix()[16..], &kmer_rec().matrix()[16..]);
+        rec.flip_col(0);
+        assert_eq!(rec.matrix(), kmer_rec().matrix());
+    }
+    fn brute_force_scores(rec: &Recognizer) -> Vec<(f64, f64)> {
+        /* every sequence the recognizer spans with its score and null probability */
+        let span = rec.len();
+        let prob = 0.25_f64.powi(span as i32);
+        (0..4_usize.pow(span as u32))
+            .map(|n| {
+                let seq: Vec<u8> = (0..span)
+                    .map(|i| b"acgt"[n / 4_usize.pow(i as u32) % 4])
+                    .collect();
+                let score = (0..rec.num_cols())
+                    .map(|i| {
+                        let kmer = kmer_idx(&seq[i..i + rec.order() + 1]).unwrap();
+                        rec.matrix()[i * rec.col_size() + kmer]
+                    })
+                    .sum();
+                (score, prob)
+            })
+            .collect()
+    }
+
+    fn brute_force_pvalue(scores: &[(f64, f64)], score: f64) -> f64 {
+        scores
+            .iter()
+            .filter(|(s, _)| *s >= score - 1e-9)
+            .map(|(_, p)| p)
+            .sum()
+    }
+
+    fn assert_pvalues_exact(rec: &Recognizer) {
+        let scores = brute_force_scores(rec);
+        for (score, _) in &scores {
+            let expected = brute_force_pvalue(&scores, *score);
+            assert!((rec.score_pvalue(*score).unwrap() - expected).abs() < 1e-12);
+        }
+        for pvalue in [0.5, 0.1, 0.01, 0.001] {
+            let threshold = rec.threshold_for_pvalue(pvalue).unwrap();
+            let expected = scores
+                .iter()
+                .map(|(s, _)| brute_force_pvalue(&scores, *s))
+                .filter(|p| *p <= pvalue)
+                .fold(0.0, f64::max);
+            assert!((brute_force_pvalue(&scores, threshold) - expected).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn pvalues_match_brute_force() {
+        /* three decimals are exact at the first granularity, five need refinement */
+        let coarse: Vec<f64> = (0..16)
+            .map(|i| (i * 37 % 23) as f64 * 0.137 - 1.0)
+            .collect();
+        assert_pvalues_exact(&pssm(RecognizerFeat::Sequence, 4, Some(coarse), None));
+        let fine: Vec<f64> = (0..24)
+            .map(|i| (i * 37 % 23) as f64 * 0.01371 - 0.1)
+            .collect();
+        assert_pvalues_exact(&pssm(RecognizerFeat::Sequence, 6, Some(fine), None));
+        let kmer: Vec<f64> = (0..48)
+            .map(|i| (i * 29 % 31) as f64 * 0.0123 - 0.2)
+            .collect();
+        assert_pvalues_exact(&pssm(RecognizerFeat::Kmer(1), 4, Some(kmer), None));
+    }
+
+    #[test]
+    fn pvalues_reject_shape_recognizers() {
+        let rec = shape(RecognizerFeat::Shape(ShapeFeat::MGW), 4, None, None, None);
+        assert!(matches!(
+            rec.score_pvalue(0.0),
+            Err(RecognizerError::UnsupportedFeatError(_))
+        ));
+    }
+
+    #[test]
+    fn column_mutations_keep_log_odds_normalised() {
+        use rand::SeedableRng;
+        let mut rng = rand_chacha::ChaCha12Rng::seed_from_u64(3);
+        let mut rec = kmer_rec();
+        rec.random_col(0, &mut rng);
+        rec.random_col(1, &mut rng);
+        for _ in 0..50 {
+            rec.mutate_col(rng.gen_range(0..2), &mut rng);
+        }
+        for col in rec.matrix().chunks(rec.col_size()) {
+            let total: f64 = col.iter().map(|s| s.exp2() / 16.0).sum();
+            assert!((total - 1.0).abs() < 1e-9);
+        }
     }
 }
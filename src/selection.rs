@@ -0,0 +1,156 @@
+use crate::error::EvolutionError;
+use rand::{Rng, RngCore};
+
+pub trait Selection {
+    fn select(&self, fitness: &[f64], rng: &mut dyn RngCore) -> usize;
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Tournament {
+    size: usize,
+}
+
+#[derive(Default, Copy, Clone, Debug)]
+pub struct Roulette;
+
+#[derive(Default, Copy, Clone, Debug)]
+pub struct Rank;
+
+#[derive(Copy, Clone, Debug)]
+pub struct Truncation {
+    fraction: f64,
+}
+
+impl Tournament {
+    pub fn new(size: usize) -> Tournament {
+        Tournament { size: size.max(1) }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+}
+
+impl Default for Tournament {
+    fn default() -> Tournament {
+        Tournament { size: 2 }
+    }
+}
+
+impl Truncation {
+    pub fn new(fraction: f64) -> Truncation {
+        Truncation {
+            fraction: fraction.clamp(f64::MIN_POSITIVE, 1.0),
+        }
+    }
+
+    pub fn fraction(&self) -> f64 {
+        self.fraction
+    }
+}
+
+impl Default for Truncation {
+    fn default() -> Truncation {
+        Truncation { fraction: 0.5 }
+    }
+}
+
+impl Selection for Tournament {
+    fn select(&self, fitness: &[f64], rng: &mut dyn RngCore) -> usize {
+        let mut best = rng.gen_range(0..fitness.len());
+        for _ in 1..self.size {
+            let challenger = rng.gen_range(0..fitness.len());
+            if fitness[challenger] > fitness[best] {
+                best = challenger;
+            }
+        }
+        best
+    }
+}
+
+impl Selection for Roulette {
+    fn select(&self, fitness: &[f64], rng: &mut dyn RngCore) -> usize {
+        /* fitness may be negative, so weights are taken relative to the worst finite score */
+        let min = fitness
+            .iter()
+            .copied()
+            .filter(|f| f.is_finite())
+            .fold(f64::INFINITY, f64::min);
+        let weights: Vec<f64> = fitness
+            .iter()
+            .map(|&f| if f.is_finite() { f - min } else { 0.0 })
+            .collect();
+        spin(&weights, rng)
+    }
+}
+
+impl Selection for Rank {
+    fn select(&self, fitness: &[f64], rng: &mut dyn RngCore) -> usize {
+        /* the worst organism gets weight 1, the best weight n */
+        let order = ranked(fitness);
+        let mut weights = vec![0.0; fitness.len()];
+        for (rank, &org_idx) in order.iter().rev().enumerate() {
+            weights[org_idx] = (rank + 1) as f64;
+        }
+        spin(&weights, rng)
+    }
+}
+
+impl Selection for Truncation {
+    fn select(&self, fitness: &[f64], rng: &mut dyn RngCore) -> usize {
+        let order = ranked(fitness);
+        let cutoff =
+            ((self.fraction * fitness.len() as f64).ceil() as usize).clamp(1, fitness.len());
+        order[rng.gen_range(0..cutoff)]
+    }
+}
+
+fn spin(weights: &[f64], rng: &mut dyn RngCore) -> usize {
+    let total: f64 = weights.iter().sum();
+    if total <= 0.0 || total.is_nan() {
+        return rng.gen_range(0..weights.len());
+    }
+    let mut target = rng.gen::<f64>() * total;
+    for (org_idx, weight) in weights.iter().enumerate() {
+        if target < *weight {
+            return org_idx;
+        }
+        target -= weight;
+    }
+    weights
+        .iter()
+        .rposition(|&weight| weight > 0.0)
+        .unwrap_or(0)
+}
+
+pub fn ranked(fitness: &[f64]) -> Vec<usize> {
+    /* organism indices from best to worst, ties keep population order */
+    let mut order: Vec<usize> = (0..fitness.len()).collect();
+    order.sort_by(|&a, &b| fitness[b].total_cmp(&fitness[a]));
+    order
+}
+
+pub fn from_name(name: &str) -> Result<Box<dyn Selection>, EvolutionError> {
+    /* parameterised schemes take their parameter after a colon, e.g. "tournament:4" */
+    let (method, param) = match name.split_once(':') {
+        Some((method, param)) => (method, Some(param)),
+        None => (name, None),
+    };
+    let invalid =
+        || EvolutionError::UnknownMethodError("SELECTION_METHOD".to_string(), name.to_string());
+    match (method, param) {
+        ("tournament", None) => Ok(Box::new(Tournament::default())),
+        ("tournament", Some(param)) => match param.parse::<usize>() {
+            Ok(size) if size > 0 => Ok(Box::new(Tournament::new(size))),
+            _ => Err(invalid()),
+        },
+        ("roulette", None) => Ok(Box::new(Roulette)),
+        ("rank", None) => Ok(Box::new(Rank)),
+        ("truncation", None) => Ok(Box::new(Truncation::default())),
+        ("truncation", Some(param)) => match param.parse::<f64>() {
+            Ok(frac) if frac > 0.0 && frac <= 1.0 => Ok(Box::new(Truncation::new(frac))),
+            _ => Err(invalid()),
+        },
+        _ => Err(invalid()),
+    }
+}
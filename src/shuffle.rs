@@ -0,0 +1,111 @@
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::collections::HashMap;
+
+pub fn kmer_shuffle(seq: &[u8], k: usize, rng: &mut impl Rng) -> Vec<u8> {
+    /* Altschul-Erickson: build the graph of (k-1)-mers with one edge per k-mer, pick a */
+    /* random arborescence of last exit edges rooted at the final (k-1)-mer, randomly */
+    /* order the remaining exits and walk the resulting Eulerian path */
+    let mut shuffled: Vec<u8> = seq.to_vec();
+    if k <= 1 {
+        shuffled.shuffle(rng);
+        return shuffled;
+    }
+    if seq.len() <= k {
+        return shuffled;
+    }
+
+    let mut node_idx: HashMap<&[u8], usize> = HashMap::new();
+    let mut nodes: Vec<&[u8]> = Vec::new();
+    let mut path: Vec<usize> = Vec::with_capacity(seq.len() - k + 2);
+    for i in 0..seq.len() - k + 2 {
+        let node = &seq[i..i + k - 1];
+        let idx = *node_idx.entry(node).or_insert_with(|| {
+            nodes.push(node);
+            nodes.len() - 1
+        });
+        path.push(idx);
+    }
+
+    let num_nodes = nodes.len();
+    let mut edges: Vec<Vec<usize>> = vec![Vec::new(); num_nodes];
+    for i in 0..path.len() - 1 {
+        edges[path[i]].push(path[i + 1]);
+    }
+    let start = path[0];
+    let end = path[path.len() - 1];
+
+    /* Wilson's algorithm for a uniformly random arborescence oriented towards end */
+    let mut in_tree: Vec<bool> = vec![false; num_nodes];
+    let mut last_edge: Vec<usize> = vec![0; num_nodes];
+    in_tree[end] = true;
+    for i in 0..num_nodes {
+        let mut u = i;
+        while !in_tree[u] {
+            last_edge[u] = rng.gen_range(0..edges[u].len());
+            u = edges[u][last_edge[u]];
+        }
+        let mut u = i;
+        while !in_tree[u] {
+            in_tree[u] = true;
+            u = edges[u][last_edge[u]];
+        }
+    }
+
+    for u in 0..num_nodes {
+        if u == end {
+            edges[u].shuffle(rng);
+            continue;
+        }
+        let last = edges[u].swap_remove(last_edge[u]);
+        edges[u].shuffle(rng);
+        edges[u].push(last);
+    }
+
+    for exits in edges.iter_mut() {
+        exits.reverse();
+    }
+    shuffled.truncate(k - 1);
+    let mut u = start;
+    while let Some(v) = edges[u].pop() {
+        shuffled.push(nodes[v][k - 2]);
+        u = v;
+    }
+    shuffled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha12Rng;
+
+    fn kmer_counts(seq: &[u8], k: usize) -> HashMap<&[u8], usize> {
+        let mut counts: HashMap<&[u8], usize> = HashMap::new();
+        for kmer in seq.windows(k) {
+            *counts.entry(kmer).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    #[test]
+    fn shuffle_preserves_kmer_counts() {
+        let seq = b"acgttgcaacgtacggtacctgaatcgcgatatcggcatgca";
+        let mut rng = ChaCha12Rng::seed_from_u64(7);
+        for k in 1..5 {
+            for _ in 0..20 {
+                let shuffled = kmer_shuffle(seq, k, &mut rng);
+                assert_eq!(shuffled.len(), seq.len());
+                assert_eq!(shuffled[..k - 1], seq[..k - 1]);
+                assert_eq!(kmer_counts(&shuffled, k), kmer_counts(seq, k));
+            }
+        }
+    }
+
+    #[test]
+    fn shuffle_changes_the_sequence() {
+        let seq = b"acgttgcaacgtacggtacctgaatcgcgatatcggcatgca";
+        let mut rng = ChaCha12Rng::seed_from_u64(7);
+        assert!((0..20).any(|_| kmer_shuffle(seq, 2, &mut rng) != seq[..]));
+    }
+}
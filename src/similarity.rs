@@ -0,0 +1,239 @@
+use crate::error::RecognizerError;
+use crate::recognizer::{Recognizer, RecognizerFeat};
+
+const KL_PSEUDO_COUNT: f64 = 1e-6;
+
+#[derive(Default, Copy, Clone, Debug)]
+pub enum ColumnMetric {
+    #[default]
+    Pearson,
+    SumSquared,
+    KullbackLeibler,
+}
+
+#[derive(Default, Copy, Clone, Debug)]
+pub struct Alignment {
+    offset: i64,
+    reverse: bool,
+    overlap: usize,
+    score: f64,
+}
+
+impl Alignment {
+    pub fn offset(&self) -> i64 {
+        self.offset
+    }
+
+    pub fn reverse(&self) -> bool {
+        self.reverse
+    }
+
+    pub fn overlap(&self) -> usize {
+        self.overlap
+    }
+
+    pub fn score(&self) -> f64 {
+        self.score
+    }
+}
+
+pub fn compare(
+    rec_a: &Recognizer,
+    rec_b: &Recognizer,
+    metric: ColumnMetric,
+) -> Result<Alignment, RecognizerError> {
+    /* slides rec_b along rec_a on both strands; offset is the column of rec_a that */
+    /* lines up with the first column of rec_b. scores are the mean column similarity */
+    /* over every column of both motifs, so higher is more similar for every metric; */
+    /* a column outside the overlap is scored against a flat column of its own mean. */
+    /* columns are compared as probabilities, log-odds recognizers are converted first */
+    let cols_a = columns(rec_a)?;
+    let cols_b = columns(rec_b)?;
+    let len_a = cols_a.len() as i64;
+    let len_b = cols_b.len() as i64;
+    let min_overlap = (len_a.min(len_b) + 1) / 2;
+
+    let mut best: Option<Alignment> = None;
+    for (reverse, cols_b) in [(false, cols_b.clone()), (true, reverse_complement(&cols_b))] {
+        for offset in (min_overlap - len_b)..(len_a - min_overlap + 1) {
+            let start = offset.max(0);
+            let stop = (offset + len_b).min(len_a);
+            let mut score: f64 = 0.0;
+            for (i, col_a) in cols_a.iter().enumerate() {
+                let i = i as i64;
+                score += if i >= start && i < stop {
+                    column_score(col_a, &cols_b[(i - offset) as usize], metric)
+                } else {
+                    flat_score(col_a, metric)
+                };
+            }
+            for (i, col_b) in cols_b.iter().enumerate() {
+                let i = i as i64 + offset;
+                if i < start || i >= stop {
+                    score += flat_score(col_b, metric);
+                }
+            }
+            let overlap = (stop - start) as usize;
+            let score = score / (cols_a.len() + cols_b.len() - overlap) as f64;
+            if best.is_none_or(|best| score > best.score) {
+                best = Some(Alignment {
+                    offset,
+                    reverse,
+                    overlap,
+                    score,
+                });
+            }
+        }
+    }
+
+    Ok(best.unwrap_or_default())
+}
+
+fn columns(rec: &Recognizer) -> Result<Vec<[f64; 4]>, RecognizerError> {
+    match rec.feat() {
+        RecognizerFeat::Sequence => {}
+        feat => return Err(RecognizerError::UnsupportedFeatError(feat)),
+    }
+    if rec.is_empty() {
+        return Err(RecognizerError::EmptyMatrixError);
+    }
+
+    let mut rec = rec.clone();
+    if rec.log_odds() {
+        rec.to_pwm(None);
+    }
+    let matrix = rec.matrix();
+    Ok((0..rec.len())
+        .map(|i| {
+            [
+                matrix[i * 4],
+                matrix[i * 4 + 1],
+                matrix[i * 4 + 2],
+                matrix[i * 4 + 3],
+            ]
+        })
+        .collect())
+}
+
+fn reverse_complement(cols: &[[f64; 4]]) -> Vec<[f64; 4]> {
+    cols.iter()
+        .rev()
+        .map(|col| [col[3], col[2], col[1], col[0]])
+        .collect()
+}
+
+fn flat_score(col: &[f64; 4], metric: ColumnMetric) -> f64 {
+    let mean: f64 = col.iter().sum::<f64>() / 4.0;
+    column_score(col, &[mean; 4], metric)
+}
+
+fn column_score(col_a: &[f64; 4], col_b: &[f64; 4], metric: ColumnMetric) -> f64 {
+    match metric {
+        ColumnMetric::Pearson => {
+            let mean_a: f64 = col_a.iter().sum::<f64>() / 4.0;
+            let mean_b: f64 = col_b.iter().sum::<f64>() / 4.0;
+            let mut cov: f64 = 0.0;
+            let mut var_a: f64 = 0.0;
+            let mut var_b: f64 = 0.0;
+            for (a, b) in col_a.iter().zip(col_b) {
+                cov += (a - mean_a) * (b - mean_b);
+                var_a += (a - mean_a).powi(2);
+                var_b += (b - mean_b).powi(2);
+            }
+            if var_a <= 0.0 || var_b <= 0.0 {
+                return 0.0;
+            }
+            cov / (var_a * var_b).sqrt()
+        }
+        ColumnMetric::SumSquared => {
+            let mut ssd: f64 = 0.0;
+            for (a, b) in col_a.iter().zip(col_b) {
+                ssd += (a - b).powi(2);
+            }
+            -ssd
+        }
+        ColumnMetric::KullbackLeibler => {
+            /* symmetrised so that compare(a, b) and compare(b, a) agree */
+            let total_a: f64 = col_a.iter().sum::<f64>() + 4.0 * KL_PSEUDO_COUNT;
+            let total_b: f64 = col_b.iter().sum::<f64>() + 4.0 * KL_PSEUDO_COUNT;
+            let mut kl: f64 = 0.0;
+            for (a, b) in col_a.iter().zip(col_b) {
+                let p = (a + KL_PSEUDO_COUNT) / total_a;
+                let q = (b + KL_PSEUDO_COUNT) / total_b;
+                kl += 0.5 * (p * (p / q).log2() + q * (q / p).log2());
+            }
+            -kl
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recognizer::pssm;
+
+    fn rec(cols: &[[f64; 4]]) -> Recognizer {
+        let matrix: Vec<f64> = cols.iter().flatten().copied().collect();
+        pssm(RecognizerFeat::Sequence, cols.len(), Some(matrix), None)
+    }
+
+    #[test]
+    fn overlap_covers_both_motifs() {
+        /* a lone matching column used to beat the full alignment for distance metrics */
+        let a = rec(&[
+            [0.1, 0.1, 0.7, 0.1],
+            [0.1, 0.1, 0.1, 0.7],
+            [0.1, 0.1, 0.7, 0.1],
+            [0.1, 0.1, 0.1, 0.7],
+        ]);
+        let b = rec(&[
+            [0.1, 0.1, 0.7, 0.1],
+            [0.1, 0.1, 0.1, 0.7],
+            [0.1, 0.1, 0.7, 0.1],
+            [0.1, 0.1, 0.2, 0.6],
+        ]);
+        for metric in [
+            ColumnMetric::Pearson,
+            ColumnMetric::SumSquared,
+            ColumnMetric::KullbackLeibler,
+        ] {
+            let alignment = compare(&a, &b, metric).unwrap();
+            assert_eq!(alignment.offset(), 0);
+            assert!(!alignment.reverse());
+            assert_eq!(alignment.overlap(), 4);
+        }
+    }
+
+    #[test]
+    fn identical_motifs_score_highest() {
+        let a = rec(&[[0.7, 0.1, 0.1, 0.1], [0.1, 0.1, 0.1, 0.7]]);
+        let alignment = compare(&a, &a, ColumnMetric::SumSquared).unwrap();
+        assert_eq!(alignment.offset(), 0);
+        assert_eq!(alignment.overlap(), 2);
+        assert_eq!(alignment.score(), 0.0);
+    }
+
+    #[test]
+    fn log_odds_motifs_compare_as_probabilities() {
+        let sites = ["tgacgt", "tgacgc", "tgacga"];
+        let probs = Recognizer::from_sites(&sites, 0.0, None).unwrap();
+        let log_odds = Recognizer::from_sites(&sites, 0.0, Some([0.25; 4])).unwrap();
+        assert!(log_odds.matrix().contains(&f64::NEG_INFINITY));
+        let shifted =
+            Recognizer::from_sites(&["cctgacgt", "aatgacgc"], 0.5, Some([0.25; 4])).unwrap();
+        for metric in [
+            ColumnMetric::Pearson,
+            ColumnMetric::SumSquared,
+            ColumnMetric::KullbackLeibler,
+        ] {
+            let expected = compare(&probs, &probs, metric).unwrap();
+            let alignment = compare(&log_odds, &probs, metric).unwrap();
+            assert_eq!(alignment.offset(), 0);
+            assert!((alignment.score() - expected.score()).abs() < 1e-9);
+
+            let alignment = compare(&shifted, &log_odds, metric).unwrap();
+            assert!(alignment.score().is_finite());
+            assert_eq!((alignment.offset(), alignment.reverse()), (2, false));
+        }
+    }
+}